@@ -0,0 +1,254 @@
+use markup5ever_rcdom::{Handle, NodeData};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::core::url::{percent_decode, remove_dot_segments, split_origin, split_reference};
+use crate::core::utils::parse;
+use crate::extract_links::{try_extract, Link};
+use crate::path_to_url::ConvertOptions;
+use crate::Error;
+
+mod tests;
+
+// extensions of html files to check
+const HTML_EXTENSIONS: [&str; 2] = ["html", "htm"];
+// file served for directory
+const INDEX_FILE: &str = "index.html";
+
+/// why link is broken
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BrokenReason {
+    /// target file does not exist
+    MissingFile,
+    /// target file exists but has no element with the id of `#fragment`
+    MissingFragment,
+}
+
+/// internal link whose target is not found
+#[derive(Clone, Debug, PartialEq)]
+pub struct BrokenLink {
+    /// html file having the link, relative to site directory
+    pub source: PathBuf,
+    /// the link including its element position
+    pub link: Link,
+    pub reason: BrokenReason,
+}
+
+/// html file or directory which could not be read and was skipped
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReadError {
+    /// path relative to site directory
+    pub source: PathBuf,
+    /// such as invalid UTF-8 or permission denied
+    pub message: String,
+}
+
+/// result of checking
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CheckReport {
+    /// number of checked html files
+    pub files: usize,
+    /// number of checked internal links
+    pub links: usize,
+    /// broken links in order of file path and document
+    pub broken: Vec<BrokenLink>,
+    /// skipped files and directories in order of path
+    pub errors: Vec<ReadError>,
+}
+
+/// checks every internal link in html files under site directory resolves to existing file
+///
+/// [feature entry point]
+///
+/// each html file is read as served at its path from site root, and links are resolved
+/// as `path_to_url` does (`current_path` of `options` is replaced). links to directory fall back
+/// to `index.html`, and `#fragment` must be `id` (or `name` of `a`) in the target html.
+/// use `set_tags(ConvertTag::all())` to check assets too.
+///
+/// ```rust,no_run
+/// use htmlproc::check_links::check;
+/// use htmlproc::path_to_url::{ConvertOptions, ConvertTag};
+///
+/// let options = ConvertOptions::new("some.domain").set_tags(ConvertTag::all());
+/// let report = check("public", &options).unwrap();
+/// for broken in report.broken {
+///     println!("{}: {} at {}", broken.source.display(), broken.link.raw, broken.link.path);
+/// }
+/// ```
+///
+/// returns error when reading site directory fails or `options` is invalid. html files and
+/// subdirectories which can't be read, such as non-UTF-8 files, are skipped and listed in
+/// `errors` of report. symlinked directories are checked once.
+pub fn check<P: AsRef<Path>>(site_dir: P, options: &ConvertOptions) -> Result<CheckReport, Error> {
+    let site_dir = site_dir.as_ref();
+    let relative = |path: &Path| path.strip_prefix(site_dir).unwrap_or(path).to_path_buf();
+    let mut files = Vec::new();
+    let mut dir_errors = Vec::new();
+    html_files(site_dir, &mut HashSet::new(), &mut files, &mut dir_errors)?;
+
+    let mut report = CheckReport::default();
+    let mut read_errors = dir_errors
+        .into_iter()
+        .map(|(path, err)| (path, err.to_string()))
+        .collect::<Vec<(PathBuf, String)>>();
+    // `None` on unreadable file, which is reported as source
+    let mut ids_cache: HashMap<PathBuf, Option<HashSet<String>>> = HashMap::new();
+    for file in files {
+        let source = relative(&file);
+        let current_path = url_path(&source);
+        let html = match read_html(&file) {
+            Ok(html) => html,
+            Err(err) => {
+                read_errors.push((file, err.to_string()));
+                continue;
+            }
+        };
+        let links = try_extract(&html, &options.clone().set_current_path(&current_path))?;
+
+        for link in links.into_iter().filter(|x| x.internal) {
+            report.links += 1;
+            let (path, _, fragment) = match split_origin(&link.resolved) {
+                Some((_, rest)) => split_reference(rest),
+                None => continue,
+            };
+            let reason = match target_file(site_dir, &percent_decode(path)) {
+                None => Some(BrokenReason::MissingFile),
+                Some(target) => {
+                    let fragment = percent_decode(fragment.trim_start_matches('#'));
+                    if fragment.is_empty()
+                        || fragment.eq_ignore_ascii_case("top")
+                        || !is_html_file(&target)
+                    {
+                        None
+                    } else {
+                        if !ids_cache.contains_key(&target) {
+                            let ids = read_html(&target).ok().map(|x| element_ids(&x));
+                            ids_cache.insert(target.clone(), ids);
+                        }
+                        match &ids_cache[&target] {
+                            Some(ids) if !ids.contains(&fragment) => {
+                                Some(BrokenReason::MissingFragment)
+                            }
+                            _ => None,
+                        }
+                    }
+                }
+            };
+            if let Some(reason) = reason {
+                report.broken.push(BrokenLink {
+                    source: source.clone(),
+                    link,
+                    reason,
+                });
+            }
+        }
+        report.files += 1;
+    }
+
+    read_errors.sort_by(|a, b| a.0.cmp(&b.0));
+    report.errors = read_errors
+        .into_iter()
+        .map(|(path, message)| ReadError {
+            source: relative(&path),
+            message,
+        })
+        .collect();
+    Ok(report)
+}
+
+/// collects html files under directory recursively in path order.
+/// directories already visited via symlink are skipped, and unreadable subdirectories are
+/// collected to `errors`
+fn html_files(
+    dir: &Path,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+    errors: &mut Vec<(PathBuf, Error)>,
+) -> Result<(), Error> {
+    if !visited.insert(fs::canonicalize(dir)?) {
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(dir)?
+        .map(|x| x.map(|x| x.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            if let Err(err) = html_files(&entry, visited, files, errors) {
+                errors.push((entry, err));
+            }
+        } else if is_html_file(&entry) {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+fn is_html_file(path: &Path) -> bool {
+    path.extension().and_then(|x| x.to_str()).is_some_and(|x| {
+        HTML_EXTENSIONS
+            .iter()
+            .any(|ext| x.eq_ignore_ascii_case(ext))
+    })
+}
+
+fn read_html(path: &Path) -> Result<String, Error> {
+    let bytes = fs::read(path)?;
+    let html = std::str::from_utf8(&bytes)?;
+    Ok(html.to_owned())
+}
+
+/// url path of file relative to site directory such as `/docs/index.html`
+fn url_path(source: &Path) -> String {
+    let segments = source
+        .components()
+        .map(|x| x.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    format!("/{}", segments.join("/"))
+}
+
+/// existing file served at url path, falling back to `index.html` on directory
+fn target_file(site_dir: &Path, path: &str) -> Option<PathBuf> {
+    // decoded path may have dot-segments such as `%2e%2e`
+    let path = remove_dot_segments(path);
+    let mut target = site_dir.to_path_buf();
+    target.extend(path.split('/').filter(|x| !x.is_empty()));
+    if target.is_dir() {
+        target.push(INDEX_FILE);
+    }
+    if target.is_file() {
+        Some(target)
+    } else {
+        None
+    }
+}
+
+/// `id` of all elements and `name` of `a` in html
+fn element_ids(html: &str) -> HashSet<String> {
+    let dom = parse(html);
+    let mut ids = HashSet::new();
+    collect_ids(&dom.document, &mut ids);
+    ids
+}
+
+fn collect_ids(handle: &Handle, ids: &mut HashSet<String>) {
+    // `template` contents are not rendered, so they are not collected
+    if let NodeData::Element {
+        ref name,
+        ref attrs,
+        ..
+    } = handle.data
+    {
+        for attr in attrs.borrow().iter() {
+            let attr_name = attr.name.local.as_ref();
+            if attr_name == "id" || (attr_name == "name" && name.local.as_ref() == "a") {
+                ids.insert(attr.value.to_string());
+            }
+        }
+    }
+    for child in handle.children.borrow().iter() {
+        collect_ids(child, ids);
+    }
+}
@@ -0,0 +1,188 @@
+/// `cargo test --features check_links`
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crate::check_links::{check, BrokenReason};
+    use crate::path_to_url::{ConvertOptions, ConvertTag};
+    use crate::Error;
+
+    /// creates site directory with files under temp dir
+    fn site(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let site_dir = std::env::temp_dir().join(format!(
+            "htmlproc-check_links-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&site_dir);
+        for (path, content) in files {
+            let path = site_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        site_dir
+    }
+
+    fn broken(site_dir: &Path, options: &ConvertOptions) -> Vec<(String, String, BrokenReason)> {
+        check(site_dir, options)
+            .unwrap()
+            .broken
+            .into_iter()
+            .map(|x| {
+                (
+                    x.source.to_string_lossy().replace('\\', "/"),
+                    x.link.raw,
+                    x.reason,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_1() {
+        let site_dir = site(
+            "check_1",
+            &[
+                ("index.html", "<a href=\"docs/\">docs</a><a href=\"/missing.html\">missing</a><a href=\"https://other.domain/x\">external</a><a href=\"https://some.domain/docs/page.html\">own</a>"),
+                ("docs/index.html", "<a href=\"page.html\">page</a><a href=\"../index.html\">top</a>"),
+                ("docs/page.html", "<p>page</p>"),
+            ],
+        );
+        let options = ConvertOptions::new("some.domain");
+
+        let report = check(&site_dir, &options).unwrap();
+        assert_eq!(report.files, 3);
+        assert_eq!(report.links, 5);
+        assert_eq!(
+            broken(&site_dir, &options),
+            vec![(
+                String::from("index.html"),
+                String::from("/missing.html"),
+                BrokenReason::MissingFile
+            )]
+        );
+        fs::remove_dir_all(site_dir).unwrap();
+    }
+
+    #[test]
+    fn check_pass_through_schemes_1() {
+        let site_dir = site(
+            "check_pass_through_schemes_1",
+            &[(
+                "index.html",
+                "<a href=\"my_app:open\">app</a><a href=\"other_app:open\">other</a><a href=\" index.html \">self</a>",
+            )],
+        );
+        let options = ConvertOptions::new("some.domain").set_pass_through_schemes(&["my_app"]);
+
+        let report = check(&site_dir, &options).unwrap();
+        assert_eq!(report.links, 2);
+        assert_eq!(
+            broken(&site_dir, &options),
+            vec![(
+                String::from("index.html"),
+                String::from("other_app:open"),
+                BrokenReason::MissingFile
+            )]
+        );
+        fs::remove_dir_all(site_dir).unwrap();
+    }
+
+    #[test]
+    fn check_fragment_1() {
+        let site_dir = site(
+            "check_fragment_1",
+            &[
+                ("index.html", "<a href=\"#intro\">intro</a><a href=\"#nowhere\">nowhere</a><a href=\"#top\">top</a><a href=\"page.html#a\">a</a><a href=\"page.html#b\">b</a><h2 id=\"intro\">intro</h2>"),
+                ("page.html", "<a name=\"a\">a</a><template><p id=\"b\"></p></template>"),
+            ],
+        );
+        let options = ConvertOptions::new("some.domain");
+
+        assert_eq!(
+            broken(&site_dir, &options),
+            vec![
+                (
+                    String::from("index.html"),
+                    String::from("#nowhere"),
+                    BrokenReason::MissingFragment
+                ),
+                (
+                    String::from("index.html"),
+                    String::from("page.html#b"),
+                    BrokenReason::MissingFragment
+                ),
+            ]
+        );
+        fs::remove_dir_all(site_dir).unwrap();
+    }
+
+    #[test]
+    fn check_assets_1() {
+        let site_dir = site(
+            "check_assets_1",
+            &[
+                ("blog/post.html", "<div><img src=\"../img/a%20b.png\"><img src=\"../img/c.png\"></div><style>body { background: url(/img/bg.png) }</style>"),
+                ("img/a b.png", ""),
+            ],
+        );
+        let options = ConvertOptions::new("some.domain").set_tags(ConvertTag::all());
+
+        let report = check(&site_dir, &options).unwrap();
+        let result = report
+            .broken
+            .iter()
+            .map(|x| (x.link.raw.as_str(), x.link.path.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            vec![
+                ("../img/c.png", "html > body > div > img:nth-of-type(2)"),
+                ("/img/bg.png", "html > body > style"),
+            ]
+        );
+        fs::remove_dir_all(site_dir).unwrap();
+    }
+
+    #[test]
+    fn check_error_1() {
+        let site_dir = site("check_error_1", &[("index.html", "")]);
+        fs::write(site_dir.join("broken.html"), [0xff, 0xfe]).unwrap();
+
+        let report = check(&site_dir, &ConvertOptions::new("some.domain")).unwrap();
+        assert_eq!(report.files, 1);
+        let result = report
+            .errors
+            .iter()
+            .map(|x| x.source.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(result, vec!["broken.html"]);
+
+        let result = check(
+            site_dir.join("nowhere"),
+            &ConvertOptions::new("some.domain"),
+        );
+        assert!(matches!(result, Err(Error::Io(_))));
+        fs::remove_dir_all(site_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_cycle_1() {
+        let site_dir = site(
+            "symlink_cycle_1",
+            &[
+                ("index.html", "<a href=\"/docs/a.html\">a</a>"),
+                ("docs/a.html", "<a href=\"/docs/loop/a.html\">a</a>"),
+            ],
+        );
+        std::os::unix::fs::symlink(site_dir.join("docs"), site_dir.join("docs/loop")).unwrap();
+
+        let report = check(&site_dir, &ConvertOptions::new("some.domain")).unwrap();
+        assert_eq!(report.files, 2);
+        assert!(report.broken.is_empty());
+        assert!(report.errors.is_empty());
+        fs::remove_dir_all(site_dir).unwrap();
+    }
+}
@@ -0,0 +1,317 @@
+use html5ever::{Attribute, Namespace, QualName};
+use markup5ever_rcdom::Handle;
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use crate::core::glob::Glob;
+use crate::core::selector::Selector;
+use crate::core::utils::{attr_name, tag_namespace};
+use crate::Error;
+
+/// elements which rule applies to
+pub enum Scope {
+    /// `attr` or `*.attr`
+    All,
+    /// `tag.attr` or `ns|tag.attr` such as `svg|*.attr`. namespace is `None` on any
+    Tag(Option<Namespace>, Glob),
+    /// `selector attr`
+    Selector(Selector),
+}
+impl Scope {
+    pub fn matches(&self, name: &QualName, handle: &Handle) -> bool {
+        match self {
+            Scope::All => true,
+            Scope::Tag(ns, x) => {
+                ns.as_ref().map_or(true, |ns| *ns == name.ns)
+                    && x.is_match_ignore_ascii_case(name.local.as_ref())
+            }
+            Scope::Selector(x) => x.matches(handle),
+        }
+    }
+}
+
+/// condition on attr value
+pub enum ValuePredicate {
+    /// `[attr=value]`
+    Equals(String),
+    /// `[attr*=value]`
+    Contains(String),
+    /// `[attr^=value]`
+    Prefix(String),
+    /// `[attr$=value]`
+    Suffix(String),
+    /// `[attr~=value]`: whitespace-separated token
+    Includes(String),
+    /// `[attr|=value]`: `value` or starting with `value-`
+    DashMatch(String),
+    /// `[attr=~regex]`, available with `regex` feature
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+}
+impl ValuePredicate {
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            ValuePredicate::Equals(x) => value == x,
+            ValuePredicate::Contains(x) => value.contains(x.as_str()),
+            ValuePredicate::Prefix(x) => value.starts_with(x.as_str()),
+            ValuePredicate::Suffix(x) => value.ends_with(x.as_str()),
+            ValuePredicate::Includes(x) => value.split_ascii_whitespace().any(|y| y == x),
+            ValuePredicate::DashMatch(x) => {
+                value == x
+                    || value
+                        .strip_prefix(x.as_str())
+                        .is_some_and(|y| y.starts_with('-'))
+            }
+            #[cfg(feature = "regex")]
+            ValuePredicate::Regex(x) => x.is_match(value),
+        }
+    }
+}
+
+/// parsed attr rule such as `span.style`, `a[target=_blank]` or `div.legacy > span style`
+pub struct AttrRule {
+    pub scope: Scope,
+    pub attr: Glob,
+    pub value: Option<ValuePredicate>,
+}
+impl AttrRule {
+    pub fn matches(&self, attr: &Attribute) -> bool {
+        // prefixed such as `xlink:href`
+        self.attr.is_match_ignore_ascii_case(&attr_name(&attr.name))
+            && self.value.as_ref().map_or(true, |x| x.matches(&attr.value))
+    }
+}
+
+pub fn options<S: AsRef<str>>(attrs: &[S]) -> Result<Vec<AttrRule>, Error> {
+    attrs.iter().map(|x| rule(x.as_ref())).collect()
+}
+
+/// parses rule such as `style`, `span.style`, `a[target=_blank]` or `div.legacy > span style`
+pub fn rule(pattern: &str) -> Result<AttrRule, Error> {
+    let trimmed = pattern.trim();
+    // selector and attr separated by the last whitespace
+    if let Some(i) = last_separator(trimmed)? {
+        let (selector, attr) = trimmed.split_at(i);
+        let selector = Selector::new(selector.trim())?;
+        let attr = attr.trim();
+        let (attr_name, value) = match condition_start(attr) {
+            Some(0) => condition(attr, pattern)?,
+            _ => (plain_name(attr, pattern)?, None),
+        };
+        return Ok(AttrRule {
+            scope: Scope::Selector(selector),
+            attr: Glob::new(attr_name)?,
+            value,
+        });
+    }
+
+    // `tag[attr=value]`
+    if let Some(i) = condition_start(trimmed) {
+        let (tag, attr) = trimmed.split_at(i);
+        let (attr_name, value) = condition(attr, pattern)?;
+        let scope = match tag {
+            "" | "*" => Scope::All,
+            _ => tag_scope(tag)?,
+        };
+        return Ok(AttrRule {
+            scope,
+            attr: Glob::new(attr_name)?,
+            value,
+        });
+    }
+
+    let splitted = split_unescaped(plain_name(trimmed, pattern)?, '.');
+    match splitted.as_slice() {
+        [attr_name] | ["*", attr_name] if !attr_name.is_empty() => Ok(AttrRule {
+            scope: Scope::All,
+            attr: Glob::new(attr_name)?,
+            value: None,
+        }),
+        [tag_name, attr_name] if !tag_name.is_empty() && !attr_name.is_empty() => Ok(AttrRule {
+            scope: tag_scope(tag_name)?,
+            attr: Glob::new(attr_name)?,
+            value: None,
+        }),
+        [_] | [_, _] => Err(Error::InvalidPattern(format!(
+            "empty tag or attr found in rules: \"{}\"",
+            pattern
+        ))),
+        _ => Err(Error::InvalidPattern(format!(
+            "each item of rules should have single or none of \".\": \"{}\"",
+            pattern
+        ))),
+    }
+}
+
+/// attr name without value condition
+fn plain_name<'a>(name: &'a str, pattern: &str) -> Result<&'a str, Error> {
+    if split_unescaped(name, '=').len() > 1 {
+        return Err(Error::InvalidPattern(format!(
+            "value condition should be in brackets such as \"a[target=_blank]\": \"{}\"",
+            pattern
+        )));
+    }
+    Ok(name)
+}
+
+/// parses tag such as `span`, `h*` or `svg|*`
+fn tag_scope(tag: &str) -> Result<Scope, Error> {
+    match split_unescaped(tag, '|').as_slice() {
+        [tag_name] => Ok(Scope::Tag(None, Glob::new(tag_name)?)),
+        ["*", tag_name] => Ok(Scope::Tag(None, Glob::new(tag_name)?)),
+        [prefix, tag_name] => match tag_namespace(prefix) {
+            Some(ns) => Ok(Scope::Tag(Some(ns), Glob::new(tag_name)?)),
+            None => Err(Error::InvalidPattern(format!(
+                "namespace prefix should be html, svg, math or *: \"{}\"",
+                tag
+            ))),
+        },
+        _ => Err(Error::InvalidPattern(format!(
+            "tag should have single or none of \"|\": \"{}\"",
+            tag
+        ))),
+    }
+}
+
+/// index of `[` of value condition such as `[style*=mso-]` at the end of rule
+fn condition_start(text: &str) -> Option<usize> {
+    if !text.ends_with(']') {
+        return None;
+    }
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// splits condition such as `[style*=mso-]` into attr name and value predicate
+///
+/// `*`, `^`, `$`, `~` and `|` just before `=` are the operators such as `*=`. glob name ending
+/// with `*` is separated from operator by whitespace such as `[data-* = x]`
+fn condition<'a>(
+    condition: &'a str,
+    pattern: &str,
+) -> Result<(&'a str, Option<ValuePredicate>), Error> {
+    let inner = condition[1..condition.len() - 1].trim();
+    let error = |message: &str| {
+        Error::InvalidPattern(format!("{} in value condition: \"{}\"", message, pattern))
+    };
+
+    // attr name ends at whitespace or operator
+    let mut end = inner.len();
+    let mut escaped = false;
+    let mut chars = inner.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let operator_start = match c {
+            _ if escaped => {
+                escaped = false;
+                false
+            }
+            '\\' => {
+                escaped = true;
+                false
+            }
+            '*' | '^' | '$' | '~' | '|' => chars.peek().map(|x| x.1) == Some('='),
+            _ => c == '=' || c.is_whitespace(),
+        };
+        if operator_start {
+            end = i;
+            break;
+        }
+    }
+    let name = &inner[..end];
+    if name.is_empty() {
+        return Err(error("attr name expected"));
+    }
+    let rest = inner[end..].trim_start();
+    if rest.is_empty() {
+        return Ok((name, None));
+    }
+
+    let (operator, value) = match ["=~", "*=", "^=", "$=", "~=", "|=", "="]
+        .iter()
+        .find(|x| rest.starts_with(*x))
+    {
+        Some(operator) => (*operator, rest[operator.len()..].trim()),
+        None => return Err(error("attribute operator expected")),
+    };
+    // quotes allow whitespace, `.` and `]` in value
+    let value = match value.chars().next() {
+        Some(quote @ ('"' | '\'')) if 2 <= value.len() && value.ends_with(quote) => {
+            &value[1..value.len() - 1]
+        }
+        Some('"' | '\'') => return Err(error("unterminated string")),
+        _ => value,
+    };
+
+    let predicate = match operator {
+        "=" => ValuePredicate::Equals(value.to_owned()),
+        "*=" => ValuePredicate::Contains(value.to_owned()),
+        "^=" => ValuePredicate::Prefix(value.to_owned()),
+        "$=" => ValuePredicate::Suffix(value.to_owned()),
+        "~=" => ValuePredicate::Includes(value.to_owned()),
+        "|=" => ValuePredicate::DashMatch(value.to_owned()),
+        #[cfg(feature = "regex")]
+        _ => ValuePredicate::Regex(
+            Regex::new(value).map_err(|err| error(&format!("invalid regex: {}", err)))?,
+        ),
+        #[cfg(not(feature = "regex"))]
+        _ => return Err(error("\"=~\" requires regex feature")),
+    };
+    Ok((name, Some(predicate)))
+}
+
+/// splits text by separator not escaped with `\\`. escapes are kept
+fn split_unescaped(text: &str, separator: char) -> Vec<&str> {
+    let mut splitted = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == separator => {
+                splitted.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    splitted.push(&text[start..]);
+    splitted
+}
+
+/// index of the last whitespace outside brackets, parens and quotes
+fn last_separator(rule: &str) -> Result<Option<usize>, Error> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut separator = None;
+    for (i, c) in rule.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if Some(c) == quote => quote = None,
+            _ if quote.is_some() => {}
+            '"' | '\'' => quote = Some(c),
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            c if c.is_whitespace() && depth == 0 => separator = Some(i),
+            _ => {}
+        }
+    }
+    if quote.is_some() || depth != 0 {
+        return Err(Error::InvalidPattern(format!(
+            "unbalanced brackets or quotes in rules: \"{}\"",
+            rule
+        )));
+    }
+    Ok(separator)
+}
@@ -1,3 +1,7 @@
-pub const SELF_CLOSING_TAGS: [&str; 9] = [
-    "meta", "link", "input", "img", "source", "embed", "br", "hr", "area",
+/// void elements which have no end tag
+/// https://html.spec.whatwg.org/multipage/syntax.html#void-elements
+/// (with legacy ones which serializer treats as void)
+pub const VOID_ELEMENTS: [&str; 18] = [
+    "area", "base", "basefont", "bgsound", "br", "col", "embed", "frame", "hr", "img", "input",
+    "keygen", "link", "meta", "param", "source", "track", "wbr",
 ];
@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+/// byte ranges of urls referenced by `url()` and `@import` in css
+///
+/// comments and strings elsewhere are skipped. quotes around urls are not included
+pub fn css_url_ranges(css: &str) -> Vec<Range<usize>> {
+    let bytes = css.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            // comment
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = match css[i + 2..].find("*/") {
+                    Some(end) => i + 2 + end + 2,
+                    None => bytes.len(),
+                };
+            }
+            // string not in url() nor @import
+            b'"' | b'\'' => {
+                i = string_end(bytes, i).1;
+            }
+            // @import "x.css"
+            b'@' if starts_with_ignore_case(&bytes[i + 1..], b"import") => {
+                i += 1 + "import".len();
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                if i < bytes.len() && (bytes[i] == b'"' || bytes[i] == b'\'') {
+                    let (end, next) = string_end(bytes, i);
+                    ranges.push(i + 1..end);
+                    i = next;
+                }
+            }
+            // url(x)
+            b'u' | b'U'
+                if starts_with_ignore_case(&bytes[i..], b"url(")
+                    && (i == 0 || !is_ident_byte(bytes[i - 1])) =>
+            {
+                i += "url(".len();
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                if i < bytes.len() && (bytes[i] == b'"' || bytes[i] == b'\'') {
+                    let (end, next) = string_end(bytes, i);
+                    ranges.push(i + 1..end);
+                    i = next;
+                } else {
+                    let start = i;
+                    while i < bytes.len() && bytes[i] != b')' && !bytes[i].is_ascii_whitespace() {
+                        if bytes[i] == b'\\' {
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                    let end = i.min(bytes.len());
+                    if start < end {
+                        ranges.push(start..end);
+                    }
+                }
+                while i < bytes.len() && bytes[i] != b')' {
+                    i += 1;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    ranges
+}
+
+/// declarations such as `color: red` in style attr value, separated by `;`
+///
+/// `;` in strings, parens such as `url(data:...;base64,...)` and comments doesn't separate them
+pub fn split_declarations(style: &str) -> Vec<&str> {
+    let bytes = style.as_bytes();
+    let mut declarations = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = match style[i + 2..].find("*/") {
+                    Some(end) => i + 2 + end + 2,
+                    None => bytes.len(),
+                };
+            }
+            b'"' | b'\'' => i = string_end(bytes, i).1,
+            b'\\' => i += 2,
+            b'(' => {
+                depth += 1;
+                i += 1;
+            }
+            b')' => {
+                depth -= 1;
+                i += 1;
+            }
+            b';' if depth <= 0 => {
+                declarations.push(&style[start..i]);
+                i += 1;
+                start = i;
+            }
+            _ => i += 1,
+        }
+    }
+    declarations.push(&style[start.min(bytes.len())..]);
+
+    declarations
+}
+
+/// property name of declaration such as `color` in `color: red`, in lowercase
+pub fn property_name(declaration: &str) -> String {
+    let name = match declaration.find(':') {
+        Some(i) => &declaration[..i],
+        None => declaration,
+    };
+    name.trim().to_ascii_lowercase()
+}
+
+/// end of contents and index next to closing quote of string starting at `start`
+fn string_end(bytes: &[u8], start: usize) -> (usize, usize) {
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            c if c == quote => return (i, i + 1),
+            // unterminated string
+            b'\n' => return (i, i),
+            _ => i += 1,
+        }
+    }
+    (bytes.len(), bytes.len())
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    bytes.len() >= prefix.len() && bytes[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+fn is_ident_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'-' || c == b'_' || !c.is_ascii()
+}
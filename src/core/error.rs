@@ -0,0 +1,53 @@
+use std::fmt;
+use std::io;
+use std::str::Utf8Error;
+
+/// errors returned by `try_*` functions
+#[derive(Debug)]
+pub enum Error {
+    /// pattern such as omit attr is malformed
+    InvalidPattern(String),
+    /// input bytes of `Pipeline::run_bytes` are not valid UTF-8
+    InvalidUtf8(Utf8Error),
+    /// url conversion option is malformed
+    InvalidUrlOption(String),
+    /// reading site directory failed in `check_links`
+    Io(io::Error),
+    /// raised by third-party `Processor`
+    Processor(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidPattern(message) => write!(f, "Invalid pattern: {}", message),
+            Error::InvalidUtf8(err) => write!(f, "Invalid UTF-8 input: {}", err),
+            Error::InvalidUrlOption(message) => write!(f, "Invalid URL option: {}", message),
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Processor(err) => write!(f, "Processor error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InvalidUtf8(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::Processor(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(err: Utf8Error) -> Self {
+        Error::InvalidUtf8(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -0,0 +1,193 @@
+use crate::Error;
+
+/// piece of glob pattern
+#[derive(Clone, Debug)]
+enum Token {
+    Literal(String),
+    /// `*` matching any characters including none
+    Star,
+}
+
+/// glob pattern such as `/blog/*/index.html`
+///
+/// `*` matches any characters. `\*` and `\\` are literal `*` and `\`
+#[derive(Clone, Debug)]
+pub struct Glob {
+    tokens: Vec<Token>,
+}
+impl Glob {
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        let mut tokens = Vec::new();
+        let mut literal = String::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(escaped) => literal.push(escaped),
+                    None => {
+                        return Err(Error::InvalidPattern(format!(
+                            "pattern should not end with \"\\\": \"{}\"",
+                            pattern
+                        )))
+                    }
+                },
+                '*' => {
+                    if !literal.is_empty() {
+                        tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                    }
+                    tokens.push(Token::Star);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            tokens.push(Token::Literal(literal));
+        }
+        Ok(Glob { tokens })
+    }
+
+    /// number of `*`
+    pub fn stars(&self) -> usize {
+        self.tokens
+            .iter()
+            .filter(|x| matches!(x, Token::Star))
+            .count()
+    }
+
+    /// the pattern itself when it has no `*`
+    pub fn literal(&self) -> Option<&str> {
+        match self.tokens.as_slice() {
+            [] => Some(""),
+            [Token::Literal(literal)] => Some(literal),
+            _ => None,
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        greedy_match(&self.tokens, text, false)
+    }
+
+    /// same as `is_match` but ASCII letters match regardless of case, for tag and attr names
+    pub fn is_match_ignore_ascii_case(&self, text: &str) -> bool {
+        greedy_match(&self.tokens, text, true)
+    }
+
+    /// texts matched by each `*` when whole text matches. each `*` matches as few as possible
+    pub fn captures<'t>(&self, text: &'t str) -> Option<Vec<&'t str>> {
+        if !self.is_match(text) {
+            return None;
+        }
+        let tokens = &self.tokens;
+        let mut memo = vec![None; (tokens.len() + 1) * (text.len() + 1)];
+        let mut captures = Vec::new();
+        let mut pos = 0;
+        for (t, token) in tokens.iter().enumerate() {
+            match token {
+                Token::Literal(literal) => pos += literal.len(),
+                Token::Star => {
+                    let end = (pos..=text.len()).find(|&i| {
+                        text.is_char_boundary(i) && matches_at(tokens, text, t + 1, i, &mut memo)
+                    })?;
+                    captures.push(&text[pos..end]);
+                    pos = end;
+                }
+            }
+        }
+        Some(captures)
+    }
+}
+
+/// checks if whole text matches in linear passes
+///
+/// literals before the first `*` and after the last `*` are anchored at both ends, and each
+/// literal between them is matched at its leftmost position, which never misses a match
+fn greedy_match(tokens: &[Token], text: &str, ignore_case: bool) -> bool {
+    let mut tokens = tokens;
+    let mut text = text;
+    while let Some((Token::Literal(literal), rest)) = tokens.split_first() {
+        match strip_literal(text, literal, ignore_case) {
+            Some(rest_text) => text = rest_text,
+            None => return false,
+        }
+        tokens = rest;
+    }
+    while let Some((Token::Literal(literal), rest)) = tokens.split_last() {
+        match strip_literal_suffix(text, literal, ignore_case) {
+            Some(rest_text) => text = rest_text,
+            None => return false,
+        }
+        tokens = rest;
+    }
+    // no `*`
+    if tokens.is_empty() {
+        return text.is_empty();
+    }
+    for token in tokens {
+        if let Token::Literal(literal) = token {
+            match find_literal(text, literal, ignore_case) {
+                Some(rest_text) => text = rest_text,
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+/// checks if tokens from `t` match text from `pos` to the end. results are memoized by `(t, pos)`
+fn matches_at(
+    tokens: &[Token],
+    text: &str,
+    t: usize,
+    pos: usize,
+    memo: &mut [Option<bool>],
+) -> bool {
+    let key = t * (text.len() + 1) + pos;
+    if let Some(matched) = memo[key] {
+        return matched;
+    }
+    let matched = match tokens.get(t) {
+        None => pos == text.len(),
+        Some(Token::Literal(literal)) => {
+            text[pos..].starts_with(literal.as_str())
+                && matches_at(tokens, text, t + 1, pos + literal.len(), memo)
+        }
+        Some(Token::Star) => (pos..=text.len())
+            .any(|i| text.is_char_boundary(i) && matches_at(tokens, text, t + 1, i, memo)),
+    };
+    memo[key] = Some(matched);
+    matched
+}
+
+fn strip_literal<'t>(text: &'t str, literal: &str, ignore_case: bool) -> Option<&'t str> {
+    if !ignore_case {
+        return text.strip_prefix(literal);
+    }
+    let n = literal.len();
+    if text.is_char_boundary(n) && text[..n].eq_ignore_ascii_case(literal) {
+        Some(&text[n..])
+    } else {
+        None
+    }
+}
+
+fn strip_literal_suffix<'t>(text: &'t str, literal: &str, ignore_case: bool) -> Option<&'t str> {
+    if !ignore_case {
+        return text.strip_suffix(literal);
+    }
+    let start = text.len().checked_sub(literal.len())?;
+    if text.is_char_boundary(start) && text[start..].eq_ignore_ascii_case(literal) {
+        Some(&text[..start])
+    } else {
+        None
+    }
+}
+
+/// text after the leftmost occurrence of literal
+fn find_literal<'t>(text: &'t str, literal: &str, ignore_case: bool) -> Option<&'t str> {
+    if !ignore_case {
+        return text.find(literal).map(|i| &text[i + literal.len()..]);
+    }
+    (0..=text.len())
+        .filter(|&i| text.is_char_boundary(i))
+        .find_map(|i| strip_literal(&text[i..], literal, true))
+}
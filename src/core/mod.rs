@@ -1,2 +1,10 @@
+pub mod attr_rule;
 pub mod consts;
+pub mod css;
+pub mod error;
+pub mod glob;
+pub mod preserve;
+pub mod selector;
+pub mod serialize;
+pub mod url;
 pub mod utils;
@@ -0,0 +1,56 @@
+use markup5ever_rcdom::{Handle, NodeData};
+
+/// how to treat comments
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum CommentPolicy {
+    /// keeps all comments
+    #[default]
+    Keep,
+    /// removes all comments
+    Strip,
+    /// keeps conditional comments such as `<!--[if IE]>` and license comments such as `<!--! ... -->` only
+    KeepConditionalAndLicense,
+}
+
+/// removes comments and doctype according to options recursively
+pub fn filter_nodes(handle: &Handle, comments: CommentPolicy, doctype: bool) {
+    if comments == CommentPolicy::Keep && doctype {
+        return;
+    }
+
+    let node = handle;
+
+    if let NodeData::Element {
+        ref template_contents,
+        ..
+    } = node.data
+    {
+        if let Some(contents) = template_contents.borrow().as_ref() {
+            filter_nodes(contents, comments, doctype);
+        }
+    }
+
+    node.children
+        .borrow_mut()
+        .retain(|child| match &child.data {
+            NodeData::Comment { ref contents } => match comments {
+                CommentPolicy::Keep => true,
+                CommentPolicy::Strip => false,
+                CommentPolicy::KeepConditionalAndLicense => {
+                    is_conditional_comment(contents) || contents.starts_with('!')
+                }
+            },
+            NodeData::Doctype { .. } => doctype,
+            _ => true,
+        });
+
+    for child in node.children.borrow().iter() {
+        filter_nodes(child, comments, doctype);
+    }
+}
+
+/// `<!--[if IE]>...<![endif]-->`, `<!--[if !IE]><!-->` or `<!--<![endif]-->`
+fn is_conditional_comment(contents: &str) -> bool {
+    let contents = contents.trim_start();
+    contents.starts_with("[if ") || contents.starts_with("<![endif]")
+}
@@ -0,0 +1,411 @@
+use html5ever::Namespace;
+use markup5ever_rcdom::{Handle, NodeData};
+
+use crate::core::utils::{attr_name, parent_node, tag_namespace};
+use crate::Error;
+
+/// CSS selector list such as `a[target=_blank], div.legacy > span`
+///
+/// supports type, universal, class, id and attribute selectors, descendant and child
+/// combinators, and `:not()`. namespace prefixes are `html`, `svg` and `math`
+/// such as `svg|*` and `[xlink|href]`
+#[derive(Clone, Debug)]
+pub struct Selector {
+    complexes: Vec<Complex>,
+}
+
+/// compound selectors joined by combinators, such as `div.legacy > span`
+#[derive(Clone, Debug)]
+struct Complex {
+    /// compounds from left. combinator is the one before each compound
+    compounds: Vec<(Combinator, Compound)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Combinator {
+    /// whitespace
+    Descendant,
+    /// `>`
+    Child,
+}
+
+/// simple selectors for one element, such as `a.external[target]`
+#[derive(Clone, Debug, Default)]
+struct Compound {
+    /// `None` on `*` or omitted
+    tag: Option<String>,
+    /// `None` on `*|` or omitted
+    namespace: Option<Namespace>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+    attrs: Vec<AttrSelector>,
+    nots: Vec<Selector>,
+}
+
+#[derive(Clone, Debug)]
+struct AttrSelector {
+    name: String,
+    matcher: Option<(AttrOperator, String)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AttrOperator {
+    /// `=`
+    Equals,
+    /// `~=`
+    Includes,
+    /// `|=`
+    DashMatch,
+    /// `^=`
+    Prefix,
+    /// `$=`
+    Suffix,
+    /// `*=`
+    Contains,
+}
+
+impl Selector {
+    pub fn new(selector: &str) -> Result<Self, Error> {
+        let mut parser = Parser {
+            source: selector,
+            chars: selector.chars().collect(),
+            pos: 0,
+        };
+        let parsed = parser.selector_list()?;
+        parser.skip_whitespace();
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("unexpected character"));
+        }
+        Ok(parsed)
+    }
+
+    /// checks if element matches any of selectors
+    pub fn matches(&self, handle: &Handle) -> bool {
+        self.complexes
+            .iter()
+            .any(|x| match_complex(&x.compounds, handle))
+    }
+}
+
+/// matches last compound with element and the rest with its ancestors
+fn match_complex(compounds: &[(Combinator, Compound)], handle: &Handle) -> bool {
+    let ((combinator, compound), rest) = match compounds.split_last() {
+        Some(x) => x,
+        None => return true,
+    };
+    if !match_compound(compound, handle) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    let mut ancestor = parent_node(handle);
+    while let Some(parent) = ancestor {
+        if !matches!(parent.data, NodeData::Element { .. }) {
+            return false;
+        }
+        if match_complex(rest, &parent) {
+            return true;
+        }
+        if *combinator == Combinator::Child {
+            return false;
+        }
+        ancestor = parent_node(&parent);
+    }
+    false
+}
+
+fn match_compound(compound: &Compound, handle: &Handle) -> bool {
+    let (name, attrs) = match handle.data {
+        NodeData::Element {
+            ref name,
+            ref attrs,
+            ..
+        } => (name, attrs.borrow()),
+        _ => return false,
+    };
+    let attr_value = |name: &str| {
+        attrs
+            .iter()
+            .find(|x| attr_name(&x.name).eq_ignore_ascii_case(name))
+            .map(|x| x.value.as_ref())
+    };
+
+    if let Some(ns) = &compound.namespace {
+        if *ns != name.ns {
+            return false;
+        }
+    }
+    if let Some(tag) = &compound.tag {
+        if !name.local.as_ref().eq_ignore_ascii_case(tag) {
+            return false;
+        }
+    }
+    if !compound.ids.iter().all(|x| attr_value("id") == Some(x)) {
+        return false;
+    }
+    let classes = attr_value("class").unwrap_or_default();
+    if !compound
+        .classes
+        .iter()
+        .all(|x| classes.split_ascii_whitespace().any(|class| class == x))
+    {
+        return false;
+    }
+    let attrs_matched = compound.attrs.iter().all(|x| {
+        let value = match attr_value(&x.name) {
+            Some(value) => value,
+            None => return false,
+        };
+        match &x.matcher {
+            None => true,
+            Some((operator, expected)) => match operator {
+                AttrOperator::Equals => value == expected,
+                AttrOperator::Includes => value.split_ascii_whitespace().any(|x| x == expected),
+                AttrOperator::DashMatch => {
+                    value == expected
+                        || value
+                            .strip_prefix(expected.as_str())
+                            .is_some_and(|x| x.starts_with('-'))
+                }
+                AttrOperator::Prefix => !expected.is_empty() && value.starts_with(expected),
+                AttrOperator::Suffix => !expected.is_empty() && value.ends_with(expected),
+                AttrOperator::Contains => !expected.is_empty() && value.contains(expected),
+            },
+        }
+    });
+    if !attrs_matched {
+        return false;
+    }
+    drop(attrs);
+    compound.nots.iter().all(|x| !x.matches(handle))
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+impl Parser<'_> {
+    fn error(&self, message: &str) -> Error {
+        Error::InvalidPattern(format!(
+            "{} at {} in selector: \"{}\"",
+            message, self.pos, self.source
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+        start < self.pos
+    }
+
+    /// comma-separated complex selectors
+    fn selector_list(&mut self) -> Result<Selector, Error> {
+        let mut complexes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            complexes.push(self.complex()?);
+            self.skip_whitespace();
+            if self.peek() == Some(',') {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Ok(Selector { complexes })
+    }
+
+    fn complex(&mut self) -> Result<Complex, Error> {
+        let mut compounds = vec![(Combinator::Descendant, self.compound()?)];
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            let combinator = match self.peek() {
+                Some('>') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(',') | Some(')') | None => break,
+                Some(_) if had_whitespace => Combinator::Descendant,
+                Some(_) => return Err(self.error("unexpected character")),
+            };
+            compounds.push((combinator, self.compound()?));
+        }
+        Ok(Complex { compounds })
+    }
+
+    fn compound(&mut self) -> Result<Compound, Error> {
+        let start = self.pos;
+        let mut compound = Compound::default();
+        match self.peek() {
+            Some('*') => self.pos += 1,
+            Some(c) if is_ident_char(c) => compound.tag = Some(self.ident()?),
+            _ => {}
+        }
+        if self.pos > start && self.namespace_separator() {
+            let prefix = compound.tag.take();
+            self.pos += 1;
+            if let Some(prefix) = prefix {
+                match tag_namespace(&prefix) {
+                    Some(ns) => compound.namespace = Some(ns),
+                    None => return Err(self.error("unknown namespace prefix")),
+                }
+            }
+            match self.peek() {
+                Some('*') => self.pos += 1,
+                _ => compound.tag = Some(self.ident()?),
+            }
+        }
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.pos += 1;
+                    compound.ids.push(self.ident()?);
+                }
+                Some('.') => {
+                    self.pos += 1;
+                    compound.classes.push(self.ident()?);
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    compound.attrs.push(self.attr_selector()?);
+                }
+                Some(':') => {
+                    self.pos += 1;
+                    let pseudo = self.ident()?;
+                    if !pseudo.eq_ignore_ascii_case("not") || self.peek() != Some('(') {
+                        return Err(self.error("only :not() is supported as pseudo-class"));
+                    }
+                    self.pos += 1;
+                    compound.nots.push(self.selector_list()?);
+                    if self.peek() != Some(')') {
+                        return Err(self.error("\")\" expected"));
+                    }
+                    self.pos += 1;
+                }
+                _ => break,
+            }
+        }
+        if self.pos == start {
+            return Err(self.error("selector expected"));
+        }
+        Ok(compound)
+    }
+
+    fn attr_selector(&mut self) -> Result<AttrSelector, Error> {
+        self.skip_whitespace();
+        let mut name = self.ident()?;
+        // `xlink|href` is matched as `xlink:href`
+        if self.namespace_separator() {
+            self.pos += 1;
+            name = format!("{}:{}", name, self.ident()?);
+        }
+        self.skip_whitespace();
+        let operator = match (self.peek(), self.chars.get(self.pos + 1)) {
+            (Some(']'), _) => {
+                self.pos += 1;
+                return Ok(AttrSelector {
+                    name,
+                    matcher: None,
+                });
+            }
+            (Some('='), _) => {
+                self.pos += 1;
+                AttrOperator::Equals
+            }
+            (Some(c), Some('=')) => {
+                let operator = match c {
+                    '~' => AttrOperator::Includes,
+                    '|' => AttrOperator::DashMatch,
+                    '^' => AttrOperator::Prefix,
+                    '$' => AttrOperator::Suffix,
+                    '*' => AttrOperator::Contains,
+                    _ => return Err(self.error("attribute operator expected")),
+                };
+                self.pos += 2;
+                operator
+            }
+            _ => return Err(self.error("attribute operator expected")),
+        };
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                self.string(quote)?
+            }
+            _ => self.ident()?,
+        };
+        self.skip_whitespace();
+        if self.peek() != Some(']') {
+            return Err(self.error("\"]\" expected"));
+        }
+        self.pos += 1;
+        Ok(AttrSelector {
+            name,
+            matcher: Some((operator, value)),
+        })
+    }
+
+    /// `|` of `ns|name`, not of `|=`
+    fn namespace_separator(&self) -> bool {
+        self.peek() == Some('|') && self.chars.get(self.pos + 1) != Some(&'=')
+    }
+
+    /// identifier with `\` escapes
+    fn ident(&mut self) -> Result<String, Error> {
+        let mut ident = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                match self.chars.get(self.pos + 1) {
+                    Some(&escaped) => ident.push(escaped),
+                    None => return Err(self.error("escaped character expected")),
+                }
+                self.pos += 2;
+            } else if is_ident_char(c) {
+                ident.push(c);
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        if ident.is_empty() {
+            return Err(self.error("identifier expected"));
+        }
+        Ok(ident)
+    }
+
+    /// quoted string after opening quote
+    fn string(&mut self, quote: char) -> Result<String, Error> {
+        let mut string = String::new();
+        loop {
+            match self.peek() {
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    if let Some(&escaped) = self.chars.get(self.pos + 1) {
+                        string.push(escaped);
+                    }
+                    self.pos += 2;
+                }
+                Some(c) => {
+                    string.push(c);
+                    self.pos += 1;
+                }
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
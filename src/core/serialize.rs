@@ -0,0 +1,137 @@
+use html5ever::{namespace_url, ns};
+use markup5ever_rcdom::{Handle, NodeData};
+
+use crate::core::consts::VOID_ELEMENTS;
+use crate::core::utils::attr_name;
+
+/// elements whose text children are written without escaping
+const RAW_TEXT_TAGS: [&str; 8] = [
+    "style",
+    "script",
+    "xmp",
+    "iframe",
+    "noembed",
+    "noframes",
+    "plaintext",
+    "noscript",
+];
+
+/// how to write start tag of void element such as `br`
+#[derive(Clone, Copy, Default)]
+pub enum VoidStyle {
+    /// `<br>`
+    #[default]
+    Html,
+    /// `<br/>`
+    Slash,
+    /// `<br />`
+    SpaceSlash,
+}
+
+/// serializes children of the node into html text
+///
+/// follows the WHATWG "serializing HTML fragments" algorithm:
+/// text and attribute values are escaped, raw text elements are written as-is,
+/// `template` contents are included and namespaced attributes keep their prefix
+pub fn serialize(handle: &Handle, void_style: VoidStyle) -> String {
+    let mut output = String::new();
+    serialize_children(handle, false, void_style, &mut output);
+    output
+}
+
+fn serialize_children(handle: &Handle, raw_text: bool, void_style: VoidStyle, output: &mut String) {
+    for child in handle.children.borrow().iter() {
+        serialize_node(child, raw_text, void_style, output);
+    }
+}
+
+/// writes node recursively
+fn serialize_node(handle: &Handle, raw_text: bool, void_style: VoidStyle, output: &mut String) {
+    let node = handle;
+
+    match &node.data {
+        NodeData::Document => serialize_children(node, false, void_style, output),
+        NodeData::Element {
+            ref name,
+            ref attrs,
+            ref template_contents,
+            ..
+        } => {
+            let tag_name = name.local.as_ref();
+            let is_html = name.ns == ns!(html);
+
+            // start tag
+            output.push('<');
+            output.push_str(tag_name);
+            for attr in attrs.borrow().iter() {
+                output.push(' ');
+                output.push_str(attr_name(&attr.name).as_str());
+                output.push_str("=\"");
+                escape(&attr.value, true, output);
+                output.push('"');
+            }
+
+            if is_html && VOID_ELEMENTS.contains(&tag_name) {
+                match void_style {
+                    VoidStyle::Html => output.push('>'),
+                    VoidStyle::Slash => output.push_str("/>"),
+                    VoidStyle::SpaceSlash => output.push_str(" />"),
+                }
+                return;
+            }
+            output.push('>');
+
+            let raw_text = is_html && RAW_TEXT_TAGS.contains(&tag_name);
+            match template_contents.borrow().as_ref() {
+                Some(contents) => serialize_children(contents, raw_text, void_style, output),
+                None => serialize_children(node, raw_text, void_style, output),
+            }
+
+            // end tag
+            output.push_str("</");
+            output.push_str(tag_name);
+            output.push('>');
+        }
+        NodeData::Text { ref contents } => {
+            if raw_text {
+                output.push_str(&contents.borrow());
+            } else {
+                escape(&contents.borrow(), false, output);
+            }
+        }
+        NodeData::Comment { ref contents } => {
+            output.push_str("<!--");
+            output.push_str(contents);
+            output.push_str("-->");
+        }
+        NodeData::Doctype { ref name, .. } => {
+            output.push_str("<!DOCTYPE ");
+            output.push_str(name);
+            output.push('>');
+        }
+        NodeData::ProcessingInstruction {
+            ref target,
+            ref contents,
+        } => {
+            output.push_str("<?");
+            output.push_str(target);
+            output.push(' ');
+            output.push_str(contents);
+            output.push('>');
+        }
+    }
+}
+
+/// escapes text. `attr_mode` is for attribute values
+fn escape(text: &str, attr_mode: bool, output: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '\u{00A0}' => output.push_str("&nbsp;"),
+            '"' if attr_mode => output.push_str("&quot;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            c => output.push(c),
+        }
+    }
+}
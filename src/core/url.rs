@@ -0,0 +1,373 @@
+use std::net::Ipv6Addr;
+use std::ops::Range;
+
+use crate::Error;
+
+/// scheme of reference such as `mailto` in `mailto:x@y` (RFC 3986 section 3.1)
+pub fn scheme(reference: &str) -> Option<&str> {
+    let end = reference.find(':')?;
+    let scheme = &reference[..end];
+    let mut chars = scheme.chars();
+    let is_scheme = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+    if is_scheme {
+        Some(scheme)
+    } else {
+        None
+    }
+}
+
+/// byte ranges of urls in srcset candidate list such as `a.png 1x, b.png 2x`
+///
+/// follows the WHATWG "parse a srcset attribute" algorithm. descriptors are skipped
+pub fn srcset_url_ranges(srcset: &str) -> Vec<Range<usize>> {
+    let bytes = srcset.as_bytes();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+
+    loop {
+        // skips whitespace and commas between candidates
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b',') {
+            i += 1;
+        }
+        if bytes.len() <= i {
+            break;
+        }
+
+        let start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let mut end = i;
+
+        // url ending with commas has no descriptors
+        if bytes[end - 1] == b',' {
+            while start < end && bytes[end - 1] == b',' {
+                end -= 1;
+            }
+            if start < end {
+                ranges.push(start..end);
+            }
+            continue;
+        }
+        ranges.push(start..end);
+
+        // descriptors until comma outside parens
+        let mut in_parens = false;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'(' => in_parens = true,
+                b')' => in_parens = false,
+                b',' if !in_parens => {
+                    i += 1;
+                    break;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    ranges
+}
+
+/// byte range of url in `content` of `<meta http-equiv="refresh">` such as `5; url=/x`
+pub fn refresh_url_range(content: &str) -> Option<Range<usize>> {
+    let bytes = content.as_bytes();
+    let skip_whitespace = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        i
+    };
+
+    // delay
+    let mut i = skip_whitespace(0);
+    while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+        i += 1;
+    }
+    i = skip_whitespace(i);
+    if i < bytes.len() && (bytes[i] == b';' || bytes[i] == b',') {
+        i += 1;
+    } else {
+        return None;
+    }
+    i = skip_whitespace(i);
+
+    // optional `url=`
+    if bytes.len() >= i + 3 && bytes[i..i + 3].eq_ignore_ascii_case(b"url") {
+        let j = skip_whitespace(i + 3);
+        if j < bytes.len() && bytes[j] == b'=' {
+            i = skip_whitespace(j + 1);
+        }
+    }
+
+    let end = match bytes.get(i) {
+        Some(&quote) if quote == b'"' || quote == b'\'' => {
+            i += 1;
+            content[i..]
+                .find(quote as char)
+                .map(|x| x + i)
+                .unwrap_or(bytes.len())
+        }
+        _ => content.trim_end().len(),
+    };
+    if i < end {
+        Some(i..end)
+    } else {
+        None
+    }
+}
+
+/// splits absolute url such as `https://some.domain/path?q` into origin and rest (`/path?q`)
+///
+/// network-path reference such as `//some.domain/path` gets origin without scheme
+pub fn split_origin(url: &str) -> Option<(&str, &str)> {
+    let authority_start = match scheme(url) {
+        Some(scheme) => scheme.len() + 1,
+        None => 0,
+    };
+    if !url[authority_start..].starts_with("//") {
+        return None;
+    }
+    let authority_start = authority_start + 2;
+    let end = url[authority_start..]
+        .find(['/', '?', '#'])
+        .map(|x| x + authority_start)
+        .unwrap_or(url.len());
+    Some(url.split_at(end))
+}
+
+/// splits origin such as `https://some.domain:8080` or `//[::1]` into host and port.
+/// port is empty when omitted
+pub fn split_host_port(origin: &str) -> (&str, &str) {
+    let authority = origin.rsplit("//").next().unwrap_or_default();
+    match authority.rfind(':') {
+        Some(i) if !authority[i..].contains(']') => (&authority[..i], &authority[i + 1..]),
+        _ => (authority, ""),
+    }
+}
+
+/// checks if HTTP(S) origins such as `https://some.domain` and `https://SOME.domain:443`
+/// are the same, ignoring case and default port
+pub fn is_same_origin(origin: &str, other: &str) -> bool {
+    let normalize = |origin: &str| {
+        let scheme = scheme(origin).map(|x| x.to_ascii_lowercase());
+        let default_port = match scheme.as_deref() {
+            Some("http") => Some(80),
+            Some("https") => Some(443),
+            _ => None,
+        };
+        let (host, port) = split_host_port(origin);
+        let port = match port {
+            "" => default_port,
+            port => port.parse::<u16>().ok(),
+        };
+        (scheme, host.to_ascii_lowercase(), port)
+    };
+    normalize(origin) == normalize(other)
+}
+
+/// splits reference into path, query (with `?`) and fragment (with `#`)
+pub fn split_reference(reference: &str) -> (&str, &str, &str) {
+    let (rest, fragment) = match reference.find('#') {
+        Some(i) => reference.split_at(i),
+        None => (reference, ""),
+    };
+    let (path, query) = match rest.find('?') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    (path, query, fragment)
+}
+
+/// resolves path reference against base path (RFC 3986 section 5.2)
+///
+/// `base` is treated as directory only when it ends with `/`.
+/// query and fragment of reference are kept as they are.
+pub fn resolve(base: &str, reference: &str) -> String {
+    let (base_path, base_query, _) = split_reference(base);
+    let (path, query, fragment) = split_reference(reference);
+
+    let (path, query) = if path.is_empty() {
+        let query = if query.is_empty() { base_query } else { query };
+        (remove_dot_segments(base_path), query)
+    } else if path.starts_with('/') {
+        (remove_dot_segments(path), query)
+    } else {
+        (remove_dot_segments(&merge(base_path, path)), query)
+    };
+
+    format!("{}{}{}", path, query, fragment)
+}
+
+/// merges relative path with directory of base path (RFC 3986 section 5.2.3)
+fn merge(base_path: &str, path: &str) -> String {
+    match base_path.rfind('/') {
+        Some(i) => format!("{}{}", &base_path[..=i], path),
+        None => format!("/{}", path),
+    }
+}
+
+/// removes `.` and `..` segments (RFC 3986 section 5.2.4)
+pub fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output = String::with_capacity(path.len());
+    // holds "/" + rest of input when prefix is replaced with "/"
+    let mut replaced;
+
+    while !input.is_empty() {
+        if let Some(rest) = input
+            .strip_prefix("../")
+            .or_else(|| input.strip_prefix("./"))
+        {
+            input = rest;
+        } else if let Some(rest) = input.strip_prefix("/./") {
+            replaced = format!("/{}", rest);
+            input = replaced.as_str();
+        } else if input == "/." {
+            input = "/";
+        } else if let Some(rest) = input.strip_prefix("/../") {
+            replaced = format!("/{}", rest);
+            input = replaced.as_str();
+            pop_segment(&mut output);
+        } else if input == "/.." {
+            input = "/";
+            pop_segment(&mut output);
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            let start = if input.starts_with('/') { 1 } else { 0 };
+            let end = input[start..]
+                .find('/')
+                .map(|i| i + start)
+                .unwrap_or(input.len());
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+
+    output
+}
+
+/// removes last segment and its preceding "/" from output
+fn pop_segment(output: &mut String) {
+    match output.rfind('/') {
+        Some(i) => output.truncate(i),
+        None => output.clear(),
+    }
+}
+
+/// makes path reference such as `/assets/x.png?q#f` relative to base path such as `/docs/page`
+///
+/// `base` is treated as directory only when it ends with `/`, as `resolve` does.
+/// `resolve(base, &relativize(base, reference))` gives normalized reference back.
+pub fn relativize(base: &str, reference: &str) -> String {
+    let (base_path, _, _) = split_reference(base);
+    let (path, query, fragment) = split_reference(reference);
+    let path = remove_dot_segments(path);
+
+    let base_dirs = match base_path.rfind('/') {
+        Some(i) => base_path[..i].split('/').skip(1).collect::<Vec<&str>>(),
+        None => Vec::new(),
+    };
+    let (dirs, name) = match path.rfind('/') {
+        Some(i) => (
+            path[..i].split('/').skip(1).collect::<Vec<&str>>(),
+            &path[i + 1..],
+        ),
+        None => (Vec::new(), path.as_str()),
+    };
+
+    let common = base_dirs
+        .iter()
+        .zip(dirs.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut relative = "../".repeat(base_dirs.len() - common);
+    for dir in &dirs[common..] {
+        relative.push_str(dir);
+        relative.push('/');
+    }
+    relative.push_str(name);
+
+    // empty path means base itself, and first segment with ":" looks like scheme
+    let first_segment = relative.split('/').next().unwrap_or_default();
+    if relative.is_empty() || first_segment.contains(':') {
+        relative.insert_str(0, "./");
+    }
+
+    format!("{}{}{}", relative, query, fragment)
+}
+
+/// decodes `%XX` escapes such as `%20`. invalid UTF-8 sequence is replaced
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(|x| u8::from_str_radix(x, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                output.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                output.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// validates hosts, ports and current path of url options
+///
+/// hosts should be names or IP addresses without port such as `some.domain` or `[::1]`
+pub fn validate_url_options(
+    hosts: &[&str],
+    ports: &[u16],
+    current_path: &str,
+) -> Result<(), Error> {
+    for host in hosts {
+        if host.is_empty() {
+            return Err(Error::InvalidUrlOption(String::from("host is empty")));
+        }
+        if host
+            .chars()
+            .any(|c| c.is_whitespace() || "/?#@".contains(c))
+        {
+            return Err(Error::InvalidUrlOption(format!(
+                "host should be host name only: \"{}\"",
+                host
+            )));
+        }
+        if host.contains(':') {
+            let ip = host
+                .strip_prefix('[')
+                .and_then(|x| x.strip_suffix(']'))
+                .unwrap_or(host);
+            if ip.parse::<Ipv6Addr>().is_err() {
+                return Err(Error::InvalidUrlOption(format!(
+                    "host should not have port (use port option): \"{}\"",
+                    host
+                )));
+            }
+        }
+    }
+    if ports.contains(&0) {
+        return Err(Error::InvalidUrlOption(String::from(
+            "port should not be 0",
+        )));
+    }
+    if !current_path.starts_with('/') {
+        return Err(Error::InvalidUrlOption(format!(
+            "current_path should start with \"/\": \"{}\"",
+            current_path
+        )));
+    }
+    Ok(())
+}
@@ -1,17 +1,181 @@
-use crate::omit_enclosure::manipulate;
+use html5ever::tendril::{StrTendril, TendrilSink};
+use html5ever::tokenizer::states::RawKind;
+use html5ever::tokenizer::{
+    BufferQueue, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts,
+};
+use html5ever::{
+    namespace_url, ns, parse_document, parse_fragment, LocalName, Namespace, ParseOpts, QualName,
+};
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
 
-pub fn reset_document_outline(output: &str, html: &str) -> String {
-    let mut omit_tags = Vec::<&str>::new();
-    // omits document outline tags if necessary
-    if !html.contains("<html>") {
-        omit_tags.push("html")
+use std::rc::Rc;
+
+use crate::omit_enclosure::scan;
+
+/// parses html text into dom
+pub fn parse(html: &str) -> RcDom {
+    parse_document(RcDom::default(), ParseOpts::default()).one(html)
+}
+
+/// parses html text into dom as children of `context` element
+///
+/// the fragment is placed under a root `html` element. see `fragment_root`
+pub fn parse_as_fragment(html: &str, context: &str) -> RcDom {
+    let context_name = QualName::new(
+        None,
+        ns!(html),
+        LocalName::from(context.to_ascii_lowercase()),
+    );
+    parse_fragment(
+        RcDom::default(),
+        ParseOpts::default(),
+        context_name,
+        Vec::new(),
+    )
+    .one(html)
+}
+
+/// root node whose children are the fragment parsed by `parse_as_fragment`
+pub fn fragment_root(document: &Handle) -> Handle {
+    let children = document.children.borrow();
+    match children.as_slice() {
+        [root] if matches!(&root.data, NodeData::Element { name, .. } if name.local.as_ref() == "html") => {
+            root.clone()
+        }
+        // root is omitted by processor
+        _ => document.clone(),
+    }
+}
+
+/// removes node from its parent
+pub fn remove_node(handle: &Handle) {
+    let parent = match handle.parent.take().and_then(|x| x.upgrade()) {
+        Some(parent) => parent,
+        None => return,
     };
-    if !html.contains("<head>") {
-        omit_tags.push("head")
+    parent
+        .children
+        .borrow_mut()
+        .retain(|x| !Rc::ptr_eq(x, handle));
+}
+
+/// collects start tags of `tag_names` found by tokenizer
+struct StartTagSink<'a> {
+    tag_names: &'a [&'a str],
+    found: Vec<&'a str>,
+}
+impl TokenSink for StartTagSink<'_> {
+    type Handle = ();
+
+    fn process_token(&mut self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+        let tag = match token {
+            Token::TagToken(tag) if tag.kind == TagKind::StartTag => tag,
+            _ => return TokenSinkResult::Continue,
+        };
+        if let Some(tag_name) = self.tag_names.iter().find(|x| **x == tag.name.as_ref()) {
+            self.found.push(tag_name);
+        }
+        // text of these elements is not markup, as tree builder switches tokenizer state
+        match tag.name.as_ref() {
+            "script" => TokenSinkResult::RawData(RawKind::ScriptData),
+            "style" | "xmp" | "iframe" | "noembed" | "noframes" | "noscript" => {
+                TokenSinkResult::RawData(RawKind::Rawtext)
+            }
+            "textarea" | "title" => TokenSinkResult::RawData(RawKind::Rcdata),
+            "plaintext" => TokenSinkResult::Plaintext,
+            _ => TokenSinkResult::Continue,
+        }
+    }
+}
+
+/// which of `tag_names` such as `body` have start tags in html text, ignoring case.
+/// tags in comments, attribute values and raw text such as `script` are not counted
+pub fn start_tags<'a>(html: &str, tag_names: &'a [&'a str]) -> Vec<&'a str> {
+    let sink = StartTagSink {
+        tag_names,
+        found: Vec::new(),
     };
-    if !html.contains("<body>") {
-        omit_tags.push("body")
+    let mut tokenizer = Tokenizer::new(sink, TokenizerOpts::default());
+    let mut input = BufferQueue::default();
+    input.push_back(StrTendril::from(html));
+    let _ = tokenizer.feed(&mut input);
+    tokenizer.end();
+    tokenizer.sink.found
+}
+
+/// omits document outline tags which are not in source html
+pub fn reset_document_outline(handle: &Handle, html: &str) {
+    let outline_tags = ["html", "head", "body"];
+    let found = start_tags(html, &outline_tags);
+    let omit_tags = outline_tags
+        .into_iter()
+        // omits document outline tags if necessary
+        .filter(|x| !found.contains(x))
+        .collect::<Vec<&str>>();
+    scan(handle, omit_tags.as_slice())
+}
+
+/// parent node if any
+pub fn parent_node(handle: &Handle) -> Option<Handle> {
+    let parent = handle.parent.take();
+    handle.parent.set(parent.clone());
+    parent.and_then(|x| x.upgrade())
+}
+
+/// position of element such as `html > body > div:nth-of-type(2) > a`
+///
+/// `:nth-of-type` is added only when parent has multiple elements of the same tag
+pub fn element_path(handle: &Handle) -> String {
+    let mut segments = Vec::new();
+    let mut node = handle.clone();
+    while let NodeData::Element { ref name, .. } = node.data {
+        let tag_name = name.local.as_ref();
+        let parent = parent_node(&node);
+        let segment = match &parent {
+            Some(parent) => {
+                let siblings = parent
+                    .children
+                    .borrow()
+                    .iter()
+                    .filter(|x| {
+                        matches!(&x.data, NodeData::Element { name, .. } if name.local.as_ref() == tag_name)
+                    })
+                    .cloned()
+                    .collect::<Vec<Handle>>();
+                match siblings.iter().position(|x| Rc::ptr_eq(x, &node)) {
+                    Some(i) if 1 < siblings.len() => format!("{}:nth-of-type({})", tag_name, i + 1),
+                    _ => tag_name.to_owned(),
+                }
+            }
+            None => tag_name.to_owned(),
+        };
+        segments.push(segment);
+        match parent {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+    segments.reverse();
+    segments.join(" > ")
+}
+
+/// attribute name with its namespace prefix
+pub fn attr_name(name: &QualName) -> String {
+    let prefix = match name.ns {
+        ns!(xml) => "xml:",
+        ns!(xmlns) if name.local.as_ref() != "xmlns" => "xmlns:",
+        ns!(xlink) => "xlink:",
+        _ => "",
     };
-    let omit_tags = omit_tags.as_slice();
-    manipulate(output, omit_tags)
+    format!("{}{}", prefix, name.local)
+}
+
+/// namespace of element prefix in `ns|tag`: `html`, `svg` or `math`
+pub fn tag_namespace(prefix: &str) -> Option<Namespace> {
+    match prefix.to_ascii_lowercase().as_str() {
+        "html" => Some(ns!(html)),
+        "svg" => Some(ns!(svg)),
+        "math" => Some(ns!(mathml)),
+        _ => None,
+    }
 }
@@ -0,0 +1,130 @@
+use crate::core::url::{is_same_origin, resolve, scheme, split_origin};
+use crate::core::utils::element_path;
+use crate::path_to_url::{
+    element_attr_value, find_base, validate, visit_urls, ActualConvertOptions, ConvertOptions,
+    UrlKind, UrlRef,
+};
+use crate::Error;
+
+mod tests;
+
+/// reference found in html
+#[derive(Clone, Debug, PartialEq)]
+pub struct Link {
+    /// tag name such as `a`
+    pub tag: String,
+    /// attr name such as `href`. `None` on text of `style` element
+    pub attr: Option<String>,
+    /// how url is held
+    pub kind: UrlKind,
+    /// url as written in html
+    pub raw: String,
+    /// absolute url resolved against current path or `<base href>`
+    pub resolved: String,
+    /// `rel` of element such as `nofollow noopener`
+    pub rel: Option<String>,
+    /// resolved url is on the origin of options or not
+    pub internal: bool,
+    /// position of element such as `html > body > div:nth-of-type(2) > a`
+    pub path: String,
+}
+
+/// extracts every reference in html in document order
+///
+/// [feature entry point]
+///
+/// target tags and url resolution follow `path_to_url::ConvertOptions`.
+/// use `set_tags(ConvertTag::all())` to list all of them.
+///
+/// ```rust
+/// use htmlproc::extract_links::extract;
+/// use htmlproc::path_to_url::{ConvertOptions, ConvertTag};
+///
+/// let source: &str = "<a href=\"../about\" rel=\"nofollow\">link</a><img src=\"https://cdn.domain/x.png\">";
+/// let options = ConvertOptions::new("some.domain")
+///     .set_current_path("/docs/page")
+///     .set_tags(ConvertTag::all());
+///
+/// let links = extract(source, &options);
+/// assert_eq!(links[0].resolved, "https://some.domain/about");
+/// assert_eq!(links[0].rel.as_deref(), Some("nofollow"));
+/// assert!(links[0].internal);
+/// assert_eq!(links[1].tag, "img");
+/// assert!(!links[1].internal);
+/// ```
+///
+/// panics when `options` is invalid. use `try_extract` to handle it as error.
+pub fn extract(html: &str, options: &ConvertOptions) -> Vec<Link> {
+    try_extract(html, options).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// extracts every reference in html, returning error instead of panic
+///
+/// ```rust
+/// use htmlproc::extract_links::try_extract;
+/// use htmlproc::path_to_url::ConvertOptions;
+/// use htmlproc::Error;
+///
+/// let source: &str = "<a href=\"/somewhere\">link</a>";
+///
+/// let result = try_extract(source, &ConvertOptions::new("some.domain"));
+/// assert_eq!(result.unwrap()[0].resolved, "https://some.domain/somewhere");
+///
+/// let result = try_extract(source, &ConvertOptions::new("https://some.domain"));
+/// assert!(matches!(result, Err(Error::InvalidUrlOption(_))));
+/// ```
+///
+pub fn try_extract(html: &str, options: &ConvertOptions) -> Result<Vec<Link>, Error> {
+    validate(options)?;
+    let dom = options.input_mode().parse(html);
+
+    let base = find_base(&dom.document);
+    let base_href = base.as_ref().and_then(|x| element_attr_value(x, "href"));
+    let (url_prefix, current_path) = options.url_base(base_href.as_deref());
+    let actual_option = options.actual(&url_prefix, &current_path);
+    let origin = options.url_prefix();
+
+    let mut links = Vec::new();
+    let targets = options.url_targets();
+    visit_urls(&dom.document, &targets, &mut |x: &UrlRef| {
+        let raw = x.url.trim();
+        let resolved = resolve_url(&actual_option, raw);
+        let internal = is_http(&resolved)
+            && split_origin(&resolved).is_some_and(|(x, _)| is_same_origin(x, &origin));
+        links.push(Link {
+            tag: x.tag.to_owned(),
+            attr: x.attr.map(|x| x.to_owned()),
+            kind: x.kind,
+            raw: raw.to_owned(),
+            resolved,
+            rel: element_attr_value(x.element, "rel"),
+            internal,
+            path: element_path(x.element),
+        });
+        None
+    });
+    Ok(links)
+}
+
+/// absolute url of reference. references which `path_to_url` leaves untouched such as
+/// `mailto:` or pass-through schemes are kept as they are
+fn resolve_url(options: &ActualConvertOptions, reference: &str) -> String {
+    if reference.starts_with("//") {
+        let scheme = options.url_prefix.split("//").next().unwrap_or_default();
+        return format!("{}{}", scheme, reference);
+    }
+    // in-page links are resolved regardless of `keep_fragment_links`
+    if !reference.starts_with('#') && !options.is_path(reference) {
+        return reference.to_owned();
+    }
+    format!(
+        "{}{}",
+        options.url_prefix,
+        resolve(options.current_path, reference)
+    )
+}
+
+/// checks if url is on HTTP(S)
+fn is_http(url: &str) -> bool {
+    scheme(url).is_some_and(|x| x.eq_ignore_ascii_case("http") || x.eq_ignore_ascii_case("https"))
+}
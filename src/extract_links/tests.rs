@@ -0,0 +1,164 @@
+/// `cargo test --features extract_links`
+#[cfg(test)]
+mod tests {
+    use crate::extract_links::{extract, try_extract, Link};
+    use crate::path_to_url::{ConvertOptions, ConvertTag, UrlKind};
+    use crate::Error;
+
+    #[test]
+    fn extract_a_1() {
+        let source = "<a href=\"/somewhere\">link</a>";
+        let options = ConvertOptions::new("some.domain");
+        let expect = vec![Link {
+            tag: String::from("a"),
+            attr: Some(String::from("href")),
+            kind: UrlKind::Attr,
+            raw: String::from("/somewhere"),
+            resolved: String::from("https://some.domain/somewhere"),
+            rel: None,
+            internal: true,
+            path: String::from("html > body > a"),
+        }];
+
+        let result = extract(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn extract_a_2() {
+        let source = "<a href=\"../x?q#f\">link</a><a href=\"#top\">link</a><a href=\"//cdn.domain/y\">link</a><a href=\"mailto:x@some.domain\">mail</a><a href=\"https://SOME.domain/z\" rel=\"nofollow\">link</a>";
+        let options = ConvertOptions::new("some.domain").set_current_path("/docs/v2/page");
+
+        let result = extract(source, &options)
+            .into_iter()
+            .map(|x| (x.resolved, x.rel, x.internal))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            vec![
+                (String::from("https://some.domain/docs/x?q#f"), None, true),
+                (
+                    String::from("https://some.domain/docs/v2/page#top"),
+                    None,
+                    true
+                ),
+                (String::from("https://cdn.domain/y"), None, false),
+                (String::from("mailto:x@some.domain"), None, false),
+                (
+                    String::from("https://SOME.domain/z"),
+                    Some(String::from("nofollow")),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn internal_1() {
+        let source = "<a href=\"https://some.domain:443/x\">link</a><a href=\"HTTPS://Some.Domain/x\">link</a><a href=\"http://some.domain/x\">link</a><a href=\"https://some.domain:8443/x\">link</a><a href=\"https://user@some.domain/x\">link</a>";
+        let options = ConvertOptions::new("some.domain");
+
+        let result = extract(source, &options)
+            .into_iter()
+            .map(|x| x.internal)
+            .collect::<Vec<_>>();
+        assert_eq!(result, vec![true, true, false, false, false]);
+
+        let options = ConvertOptions::new("some.domain").set_port(8443);
+        let result = extract(source, &options)
+            .into_iter()
+            .map(|x| x.internal)
+            .collect::<Vec<_>>();
+        assert_eq!(result, vec![false, false, false, true, false]);
+    }
+
+    #[test]
+    fn extract_all_1() {
+        let source = "<link rel=\"stylesheet\" href=\"/main.css\"><style>body { background: url(bg.png) }</style><img src=\"a.png\" srcset=\"a.png 1x, b.png 2x\"><div style=\"background: url('/c.png')\"></div>";
+        let options = ConvertOptions::new("some.domain").set_tags(ConvertTag::all());
+
+        let result = extract(source, &options)
+            .into_iter()
+            .map(|x| (x.tag, x.attr, x.kind, x.raw))
+            .collect::<Vec<_>>();
+        let attr = |x: &str| Some(String::from(x));
+        assert_eq!(
+            result,
+            vec![
+                (
+                    String::from("link"),
+                    attr("href"),
+                    UrlKind::Attr,
+                    String::from("/main.css")
+                ),
+                (
+                    String::from("style"),
+                    None,
+                    UrlKind::Css,
+                    String::from("bg.png")
+                ),
+                (
+                    String::from("img"),
+                    attr("src"),
+                    UrlKind::Attr,
+                    String::from("a.png")
+                ),
+                (
+                    String::from("img"),
+                    attr("srcset"),
+                    UrlKind::Srcset,
+                    String::from("a.png")
+                ),
+                (
+                    String::from("img"),
+                    attr("srcset"),
+                    UrlKind::Srcset,
+                    String::from("b.png")
+                ),
+                (
+                    String::from("div"),
+                    attr("style"),
+                    UrlKind::Css,
+                    String::from("/c.png")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_path_1() {
+        let source = "<div><p><a href=\"/x\">link</a></p><p><a href=\"/y\">link</a><a href=\"/z\">link</a></p></div>";
+        let options = ConvertOptions::new("some.domain");
+
+        let result = extract(source, &options)
+            .into_iter()
+            .map(|x| x.path)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            vec![
+                "html > body > div > p:nth-of-type(1) > a",
+                "html > body > div > p:nth-of-type(2) > a:nth-of-type(1)",
+                "html > body > div > p:nth-of-type(2) > a:nth-of-type(2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_base_1() {
+        let source = "<base href=\"https://other.domain/docs/\"><a href=\"x\">link</a>";
+        let options = ConvertOptions::new("some.domain");
+
+        let result = extract(source, &options);
+        assert_eq!(result[0].resolved, "https://other.domain/docs/x");
+        assert!(!result[0].internal);
+    }
+
+    #[test]
+    fn invalid_option_1() {
+        let source = "<a href=\"/somewhere\">link</a>";
+
+        let result = try_extract(source, &ConvertOptions::new(""));
+        assert!(matches!(result, Err(Error::InvalidUrlOption(_))));
+    }
+}
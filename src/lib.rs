@@ -1,10 +1,27 @@
 //! HTML processors as utils.
 //! Each function is offered as a single `feature`, so the dependencies are kept small. (`omit_enclosure` which is used as document outline formatter is exception.)
 
+// tests are placed as `<module>/tests.rs` containing `mod tests`
+#![allow(clippy::module_inception)]
+
+// #[cfg(feature = "check_links")]
+pub mod check_links;
+// #[cfg(feature = "extract_links")]
+pub mod extract_links;
 // #[cfg(feature = "omit_attr")]
 pub mod omit_attr;
 pub mod omit_enclosure;
 // #[cfg(feature = "path_to_url")]
 pub mod path_to_url;
+pub mod pipeline;
+// #[cfg(feature = "rewrite_links")]
+pub mod rewrite_links;
+// #[cfg(feature = "transform_attr")]
+pub mod transform_attr;
+// #[cfg(feature = "url_to_path")]
+pub mod url_to_path;
 
 mod core;
+
+pub use crate::core::error::Error;
+pub use crate::core::preserve::CommentPolicy;
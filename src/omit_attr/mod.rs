@@ -1,14 +1,68 @@
-use html5ever::tendril::TendrilSink;
-use html5ever::{parse_document, ParseOpts};
+use html5ever::QualName;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 
-use crate::core::utils::reset_document_outline;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::attr_rule::{options, AttrRule};
+use crate::core::css::{property_name, split_declarations};
+use crate::core::glob::Glob;
+use crate::core::preserve::filter_nodes;
+use crate::core::utils::{attr_name, element_path};
+use crate::pipeline::{Pipeline, Processor};
+use crate::{CommentPolicy, Error};
 
 mod tests;
 
-struct OmitOptions {
-    attrs: Vec<String>,
-    tag_attrs: Vec<(String, String)>,
+/// parsed omit_attrs, allow_attrs and token patterns
+struct Rules {
+    omit: Vec<AttrRule>,
+    allow: Option<Vec<AttrRule>>,
+    classes: Vec<Glob>,
+    style_properties: Vec<Glob>,
+}
+
+/// changes of attrs of element found by scan
+struct ElementOmits {
+    element: Handle,
+    /// indices of attrs to omit and why
+    omits: Vec<(usize, OmitReason)>,
+    /// indices of attrs to rewrite, their new values and omitted tokens. empty values are omitted
+    rewrites: Vec<(usize, String, String)>,
+}
+
+/// why attr is omitted
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OmitReason {
+    /// matched item of `omit_attrs` at the index
+    Rule(usize),
+    /// matched none of `allow_attrs`
+    NotAllowed,
+    /// class tokens or style declarations are omitted
+    Tokens,
+}
+
+/// attr omitted from element
+#[derive(Clone, Debug, PartialEq)]
+pub struct OmittedAttr {
+    pub tag: String,
+    pub attr: String,
+    /// omitted value. only omitted class tokens or style declarations on `OmitReason::Tokens`
+    pub value: String,
+    /// position of element such as `html > body > div > span:nth-of-type(2)`
+    pub path: String,
+    pub reason: OmitReason,
+}
+
+/// result of omitting
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OmitReport {
+    /// number of attrs omitted by each item of `omit_attrs`, in order of them
+    pub fired: Vec<usize>,
+    /// number of attrs omitted by allowlist
+    pub not_allowed: usize,
+    /// omitted attrs in document order
+    pub omitted: Vec<OmittedAttr>,
 }
 
 /// omits specific attributes of tags in html
@@ -26,107 +80,350 @@ struct OmitOptions {
 /// assert_eq!(result, expect);
 /// ```
 ///
+/// each item of `omit_attrs` is `attr`, `*.attr`, `tag.attr` or `selector attr`
+/// such as `div.legacy > span style` (CSS selector followed by whitespace and attr).
+/// `*` in tag and attr names matches any characters such as `data-*` or `h*.id`.
+/// `\*` and `\.` are literal `*` and `.`
+/// tag can have namespace prefix `html|`, `svg|` or `math|` such as `svg|*.attr`, and attrs
+/// having namespace are written with prefix such as `xlink:href`. names match regardless of case.
+/// value condition is written in brackets in place of `.attr`: `[attr=value]`, `[attr*=value]`
+/// (contains), `[attr^=value]` (starts with), `[attr$=value]` (ends with), `[attr~=value]`
+/// (has token), `[attr|=value]` (equals or starts with `value-`) or `[attr=~regex]`, such as
+/// `a[target=_blank]` or `div.legacy p [style*=mso-]`. glob attr name is separated from
+/// operator by whitespace such as `[data-* = x]`. `=~` needs `regex` feature, which
+/// `omit_attr` and `transform_attr` features enable.
+///
+/// panics when `omit_attrs` has invalid pattern. use `try_manipulate` to handle it as error.
 pub fn manipulate(html: &str, omit_attrs: &[&str]) -> String {
-    let dom = parse_document(RcDom::default(), ParseOpts::default())
-        .from_utf8()
-        .read_from(&mut html.as_bytes())
-        .unwrap();
+    try_manipulate(html, omit_attrs).unwrap_or_else(|err| panic!("{}", err))
+}
 
-    let options = options(omit_attrs);
+/// omits specific attributes of tags in html, returning error instead of panic
+///
+/// ```rust
+/// use htmlproc::omit_attr::try_manipulate;
+/// use htmlproc::Error;
+///
+/// let source: &str = "<span style=\"want: omitted;\">Content</span>";
+///
+/// let result = try_manipulate(source, &["span.style"]);
+/// assert_eq!(result.unwrap(), "<span>Content</span>");
+///
+/// let result = try_manipulate(source, &["a.b.c"]);
+/// assert!(matches!(result, Err(Error::InvalidPattern(_))));
+/// ```
+///
+pub fn try_manipulate(html: &str, omit_attrs: &[&str]) -> Result<String, Error> {
+    Pipeline::new()
+        .add_processor(OmitAttr::new(omit_attrs))
+        .run(html)
+}
 
-    let mut output = String::new();
-    scan(&dom.document, &options, &mut output);
+/// omits specific attributes of tags in html, reporting what is omitted
+///
+/// ```rust
+/// use htmlproc::omit_attr::{manipulate_with_report, OmitReason};
+///
+/// let source: &str = "<div onclick=\"f()\"><img src=\"a.png\" onload=\"g()\" style=\"x: y;\"></div>";
+///
+/// let (result, report) = manipulate_with_report(source, &["on*", "style"]);
+/// assert_eq!(result, "<div><img src=\"a.png\"></div>");
+/// assert_eq!(report.fired, vec![2, 1]);
+/// assert_eq!(report.omitted[1].tag, "img");
+/// assert_eq!(report.omitted[1].attr, "onload");
+/// assert_eq!(report.omitted[1].value, "g()");
+/// assert_eq!(report.omitted[1].path, "html > body > div > img");
+/// assert_eq!(report.omitted[1].reason, OmitReason::Rule(0));
+/// ```
+///
+/// each omitted attr is counted for the first matching item of `omit_attrs`.
+///
+/// panics when `omit_attrs` has invalid pattern. use `try_manipulate_with_report` to handle it as error.
+pub fn manipulate_with_report(html: &str, omit_attrs: &[&str]) -> (String, OmitReport) {
+    try_manipulate_with_report(html, omit_attrs).unwrap_or_else(|err| panic!("{}", err))
+}
 
-    reset_document_outline(output.as_str(), html)
+/// omits specific attributes of tags in html, reporting what is omitted and returning error instead of panic
+pub fn try_manipulate_with_report(
+    html: &str,
+    omit_attrs: &[&str],
+) -> Result<(String, OmitReport), Error> {
+    let processor = OmitAttr::new(omit_attrs);
+    let report = processor.report();
+    let html = Pipeline::new().add_processor(processor).run(html)?;
+    let report = report.take();
+    Ok((html, report))
 }
 
-fn options(omit_attrs: &[&str]) -> OmitOptions {
-    if omit_attrs
-        .iter()
-        .any(|x| 1 < x.chars().filter(|y| *y == '.').count())
-    {
-        panic!("Invalid omit_attrs: each item should have single or none of \".\"");
+/// `Processor` omitting specific attributes of tags, for use in `Pipeline`
+///
+/// `set_allow_attrs` turns on allowlist mode:
+///
+/// ```rust
+/// use htmlproc::omit_attr::OmitAttr;
+/// use htmlproc::pipeline::Pipeline;
+///
+/// let source: &str = "<a href=\"/x\" title=\"t\" class=\"c\" onclick=\"f()\">x</a><img src=\"a.png\" alt=\"a\" title=\"t\" width=\"1\">";
+/// let expect: &str = "<a href=\"/x\" title=\"t\">x</a><img src=\"a.png\" alt=\"a\">";
+///
+/// let result = Pipeline::new()
+///     .add_processor(
+///         OmitAttr::new(&["img.title"]).set_allow_attrs(&["title", "a.href", "img.src", "img.alt"]),
+///     )
+///     .run(source);
+/// assert_eq!(result.unwrap(), expect);
+/// ```
+pub struct OmitAttr {
+    omit_attrs: Vec<String>,
+    /// allowlist. attrs not matching it are omitted too. Default: none (allowlist mode is off)
+    allow_attrs: Option<Vec<String>>,
+    /// class tokens to omit from `class`
+    omit_classes: Vec<String>,
+    /// css properties to omit from `style`
+    omit_style_properties: Vec<String>,
+    /// comments handling. Default: keep all
+    comments: CommentPolicy,
+    /// keeps doctype or not. Default: true
+    doctype: bool,
+    report: Rc<RefCell<OmitReport>>,
+}
+impl OmitAttr {
+    pub fn new(omit_attrs: &[&str]) -> Self {
+        OmitAttr {
+            omit_attrs: omit_attrs.iter().map(|x| x.to_string()).collect(),
+            allow_attrs: None,
+            omit_classes: Vec::new(),
+            omit_style_properties: Vec::new(),
+            comments: CommentPolicy::Keep,
+            doctype: true,
+            report: Rc::new(RefCell::new(OmitReport::default())),
+        }
+    }
+    /// report shared with this processor, filled on process
+    ///
+    /// take it before adding this processor to `Pipeline` and read it after run
+    pub fn report(&self) -> Rc<RefCell<OmitReport>> {
+        self.report.clone()
+    }
+    /// keeps only attrs matching `allow_attrs` in the same format as `omit_attrs`,
+    /// such as `title` for all tags and `a.href` for `a`.
+    /// `omit_attrs` takes precedence: attrs matching both are omitted
+    pub fn set_allow_attrs(mut self, allow_attrs: &[&str]) -> Self {
+        self.allow_attrs = Some(allow_attrs.iter().map(|x| x.to_string()).collect());
+        self
+    }
+    /// omits class tokens such as `Apple-converted-space` or `Mso*` from `class`,
+    /// keeping the others. `class` becoming empty is omitted
+    pub fn set_omit_classes(mut self, omit_classes: &[&str]) -> Self {
+        self.omit_classes = omit_classes.iter().map(|x| x.to_string()).collect();
+        self
+    }
+    /// omits declarations of css properties such as `font-family` or `-webkit-*` from `style`,
+    /// keeping the others. `style` becoming empty is omitted
+    pub fn set_omit_style_properties(mut self, omit_style_properties: &[&str]) -> Self {
+        self.omit_style_properties = omit_style_properties
+            .iter()
+            .map(|x| x.to_ascii_lowercase())
+            .collect();
+        self
     }
+    pub fn set_comments(mut self, comments: CommentPolicy) -> Self {
+        self.comments = comments;
+        self
+    }
+    pub fn set_doctype(mut self, doctype: bool) -> Self {
+        self.doctype = doctype;
+        self
+    }
+}
+impl Processor for OmitAttr {
+    fn process(&self, dom: &mut RcDom) -> Result<(), Error> {
+        let rules = Rules {
+            omit: options(&self.omit_attrs)?,
+            allow: match &self.allow_attrs {
+                Some(allow_attrs) => Some(options(allow_attrs)?),
+                None => None,
+            },
+            classes: globs(&self.omit_classes)?,
+            style_properties: globs(&self.omit_style_properties)?,
+        };
 
-    let attr_options = omit_attrs
-        .iter()
-        .filter(|&&x| !x.contains('.') || x.starts_with("*."))
-        .map(|&x| {
-            let ret = if x.starts_with("*.") { &x[2..] } else { x };
-            ret.to_owned()
-        })
-        .collect();
-    let tag_attr_options = omit_attrs
-        .iter()
-        .filter(|&&x| x.contains('.') && !x.starts_with("*."))
-        .map(|x| {
-            let splitted = x.split('.').collect::<Vec<&str>>();
-            let tag_name = splitted[0];
-            let attr_name = splitted[1];
-            if tag_name.is_empty() || attr_name.is_empty() {
-                panic!("Invalid tag-attr pair found: {} - {}", tag_name, attr_name);
+        let mut omits = Vec::new();
+        scan(&dom.document, &rules, &mut omits);
+        let mut report = OmitReport {
+            fired: vec![0; rules.omit.len()],
+            ..Default::default()
+        };
+        for element_omits in omits {
+            if let NodeData::Element {
+                ref name,
+                ref attrs,
+                ..
+            } = element_omits.element.data
+            {
+                let path = element_path(&element_omits.element);
+                let mut attrs = attrs.borrow_mut();
+                let mut omitted = Vec::new();
+                let mut record = |i: usize, value: String, reason: OmitReason| {
+                    omitted.push((
+                        i,
+                        OmittedAttr {
+                            tag: name.local.to_string(),
+                            attr: attr_name(&attrs[i].name),
+                            value,
+                            path: path.clone(),
+                            reason,
+                        },
+                    ));
+                };
+
+                let mut indices = Vec::new();
+                for (i, reason) in element_omits.omits {
+                    match reason {
+                        OmitReason::Rule(rule) => report.fired[rule] += 1,
+                        OmitReason::NotAllowed => report.not_allowed += 1,
+                        OmitReason::Tokens => {}
+                    }
+                    record(i, attrs[i].value.to_string(), reason);
+                    indices.push(i);
+                }
+                let mut rewrites = Vec::new();
+                for (i, value, tokens) in element_omits.rewrites {
+                    record(i, tokens, OmitReason::Tokens);
+                    rewrites.push((i, value));
+                }
+                omitted.sort_by_key(|x| x.0);
+                report.omitted.extend(omitted.into_iter().map(|x| x.1));
+
+                for (i, value) in rewrites {
+                    if value.is_empty() {
+                        indices.push(i);
+                    } else {
+                        attrs[i].value = value.into();
+                    }
+                }
+                indices.sort_unstable();
+                for i in indices.into_iter().rev() {
+                    attrs.remove(i);
+                }
             }
-            (tag_name.to_owned(), attr_name.to_owned())
-        })
-        .collect();
-    let options = OmitOptions {
-        attrs: attr_options,
-        tag_attrs: tag_attr_options,
-    };
-    options
+        }
+        *self.report.borrow_mut() = report;
+        filter_nodes(&dom.document, self.comments, self.doctype);
+        Ok(())
+    }
+}
+
+fn globs(patterns: &[String]) -> Result<Vec<Glob>, Error> {
+    patterns.iter().map(|x| Glob::new(x)).collect()
 }
 
-/// scan to manipulate dom recursively
-fn scan(handle: &Handle, options: &OmitOptions, output: &mut String) {
+/// scan to find attrs to omit recursively. attrs are matched against the source document
+fn scan(handle: &Handle, rules: &Rules, omits: &mut Vec<ElementOmits>) {
     let node = handle;
 
-    match &node.data {
-        NodeData::Document => {
-            for child in node.children.borrow().iter() {
-                scan(child, options, output);
+    if let NodeData::Element {
+        ref name,
+        ref attrs,
+        ref template_contents,
+        ..
+    } = node.data
+    {
+        let omit_rules = scoped_rules(&rules.omit, name, node);
+        let allow_rules = rules.allow.as_deref().map(|x| scoped_rules(x, name, node));
+
+        let mut element_omits = ElementOmits {
+            element: node.clone(),
+            omits: Vec::new(),
+            rewrites: Vec::new(),
+        };
+        for (i, attr) in attrs.borrow().iter().enumerate() {
+            let reason = match omit_rules.iter().find(|(_, x)| x.matches(attr)) {
+                Some((rule, _)) => Some(OmitReason::Rule(*rule)),
+                None if allow_rules
+                    .as_ref()
+                    .is_some_and(|allow| !allow.iter().any(|(_, x)| x.matches(attr))) =>
+                {
+                    Some(OmitReason::NotAllowed)
+                }
+                None => None,
+            };
+            if let Some(reason) = reason {
+                element_omits.omits.push((i, reason));
+                continue;
             }
-        }
-        NodeData::Element {
-            ref name,
-            ref attrs,
-            ..
-        } => {
-            let tag_name = name.local.to_string();
-
-            // start tag
-            output.push('<');
-            output.push_str(tag_name.as_str());
-
-            let attrs = attrs
-                .clone()
-                .into_inner()
-                .iter()
-                .filter(|x| {
-                    let attr_name = x.name.local.to_string();
-                    !options.attrs.contains(&attr_name)
-                        && !options
-                            .tag_attrs
-                            .contains(&(tag_name.to_owned(), attr_name))
-                })
-                .map(|x| format!(" {}=\"{}\"", x.name.local, x.value))
-                .collect::<Vec<String>>()
-                .join("");
-            output.push_str(attrs.as_str());
-
-            output.push('>');
-
-            for child in node.children.borrow().iter() {
-                scan(child, options, output);
+            let rewritten = match attr_name(&attr.name).as_str() {
+                "class" if !rules.classes.is_empty() => omit_classes(&attr.value, &rules.classes),
+                "style" if !rules.style_properties.is_empty() => {
+                    omit_style_properties(&attr.value, &rules.style_properties)
+                }
+                _ => None,
+            };
+            if let Some((value, tokens)) = rewritten {
+                element_omits.rewrites.push((i, value, tokens));
             }
-
-            // end tag
-            output.push_str("</");
-            output.push_str(tag_name.as_str());
-            output.push('>');
         }
-        NodeData::Text { ref contents } => {
-            output.push_str(&contents.borrow());
+        if !element_omits.omits.is_empty() || !element_omits.rewrites.is_empty() {
+            omits.push(element_omits);
+        }
+
+        if let Some(contents) = template_contents.borrow().as_ref() {
+            scan(contents, rules, omits);
         }
-        _ => {}
     }
+
+    for child in node.children.borrow().iter() {
+        scan(child, rules, omits);
+    }
+}
+
+/// rules whose scope matches element, with their indices
+fn scoped_rules<'a>(
+    rules: &'a [AttrRule],
+    name: &QualName,
+    handle: &Handle,
+) -> Vec<(usize, &'a AttrRule)> {
+    rules
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| x.scope.matches(name, handle))
+        .collect()
+}
+
+/// class value without tokens matching patterns, and the omitted tokens.
+/// `None` when nothing is omitted
+fn omit_classes(value: &str, patterns: &[Glob]) -> Option<(String, String)> {
+    let (omitted, kept): (Vec<&str>, Vec<&str>) = value
+        .split_ascii_whitespace()
+        .partition(|x| patterns.iter().any(|pattern| pattern.is_match(x)));
+    if omitted.is_empty() {
+        return None;
+    }
+    Some((kept.join(" "), omitted.join(" ")))
+}
+
+/// style value without declarations of properties matching patterns, and the omitted declarations.
+/// `None` when nothing is omitted
+fn omit_style_properties(value: &str, patterns: &[Glob]) -> Option<(String, String)> {
+    let (omitted, kept): (Vec<&str>, Vec<&str>) = split_declarations(value)
+        .into_iter()
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .partition(|x| {
+            let name = property_name(x);
+            patterns.iter().any(|pattern| pattern.is_match(&name))
+        });
+    if omitted.is_empty() {
+        return None;
+    }
+    let omitted = omitted.join("; ");
+    if kept.is_empty() {
+        return Some((String::new(), omitted));
+    }
+    let terminator = if value.trim_end().ends_with(';') {
+        ";"
+    } else {
+        ""
+    };
+    Some((format!("{}{}", kept.join("; "), terminator), omitted))
 }
@@ -1,7 +1,12 @@
 /// `cargo test --features omit_attr`
 #[cfg(test)]
 mod tests {
-    use crate::omit_attr::manipulate;
+    use crate::omit_attr::{
+        manipulate, manipulate_with_report, try_manipulate, try_manipulate_with_report, OmitAttr,
+        OmitReason, OmittedAttr,
+    };
+    use crate::pipeline::Pipeline;
+    use crate::{CommentPolicy, Error};
 
     #[test]
     fn manipulate_attr_1() {
@@ -82,4 +87,670 @@ mod tests {
         let result = manipulate(source, omits);
         assert_eq!(result, expect);
     }
+
+    #[test]
+    fn serialize_escaped_1() {
+        let source = "<span title=\"say &quot;hi&quot; &amp; bye\" style=\"remove: me;\">a &lt; b &amp;&amp; c &gt; d</span>";
+        let omits = &["style"];
+        let expect =
+            "<span title=\"say &quot;hi&quot; &amp; bye\">a &lt; b &amp;&amp; c &gt; d</span>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn serialize_raw_text_1() {
+        let source = "<script>if (a < b && c) {}</script><style>a > b { color: red; }</style>";
+        let omits = &["style"];
+        let expect = "<script>if (a < b && c) {}</script><style>a > b { color: red; }</style>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn serialize_template_1() {
+        let source = "<template><p style=\"remove: me;\">Content</p></template>";
+        let omits = &["style"];
+        let expect = "<template><p>Content</p></template>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn serialize_comment_1() {
+        let source = "<!-- note --><span style=\"remove: me;\">Content</span>";
+        let omits = &["style"];
+        let expect = "<!-- note --><span>Content</span>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn serialize_namespaced_attr_1() {
+        let source = "<svg><use xlink:href=\"#icon\" style=\"remove: me;\"></use></svg>";
+        let omits = &["style"];
+        let expect = "<svg><use xlink:href=\"#icon\"></use></svg>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn try_manipulate_1() {
+        let source = "<span style=\"remove: me;\" class=\"keep-me\">Content</span>";
+        let omits = &["span.style"];
+        let expect = "<span class=\"keep-me\">Content</span>";
+
+        let result = try_manipulate(source, omits);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn invalid_pattern_1() {
+        let source = "<span style=\"remove: me;\">Content</span>";
+
+        for omits in [&["a.b.c"], &[".style"], &["span."], &[""], &["*."]] {
+            let result = try_manipulate(source, omits);
+            assert!(matches!(result, Err(Error::InvalidPattern(_))));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid pattern")]
+    fn invalid_pattern_2() {
+        let source = "<span style=\"remove: me;\">Content</span>";
+        let omits = &["a.b.c"];
+
+        manipulate(source, omits);
+    }
+
+    #[test]
+    fn preserve_1() {
+        let source = "<!DOCTYPE html><!--[if IE]><p>IE</p><![endif]--><span style=\"remove: me;\">Content</span>";
+        let omits = &["style"];
+        let expect = "<!DOCTYPE html><!--[if IE]><p>IE</p><![endif]--><span>Content</span>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn processing_instruction_as_comment_1() {
+        // HTML parser reads processing instruction as bogus comment, so it isn't preserved
+        let source = "<span style=\"remove: me;\">Content</span><?php echo 1; ?>";
+        let omits = &["style"];
+        let expect = "<span>Content</span><!--?php echo 1; ?-->";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn preserve_2() {
+        let source =
+            "<!DOCTYPE html><!-- note --><span style=\"remove: me;\">Content<!-- inner --></span>";
+        let expect = "<!DOCTYPE html><span>Content</span>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&["style"]).set_comments(CommentPolicy::Strip))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn void_elements_1() {
+        let source = "<p style=\"remove: me;\">a<br>b<wbr>c<img src=\"x.png\" style=\"remove: me;\"></p><table><colgroup><col span=\"2\"></colgroup></table>";
+        let omits = &["style"];
+        let expect = "<p>a<br>b<wbr>c<img src=\"x.png\"></p><table><colgroup><col span=\"2\"></colgroup></table>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn selector_1() {
+        let source = "<a href=\"/x\" target=\"_blank\" rel=\"opener\">x</a><a href=\"/y\" target=\"_self\" rel=\"opener\">y</a>";
+        let omits = &["a[target=_blank] rel"];
+        let expect = "<a href=\"/x\" target=\"_blank\">x</a><a href=\"/y\" target=\"_self\" rel=\"opener\">y</a>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn selector_2() {
+        let source = "<div class=\"legacy old\"><span style=\"a\">x</span><p><span style=\"b\">y</span></p></div><div><span style=\"c\">z</span></div>";
+        let omits = &["div.legacy > span style"];
+        let expect = "<div class=\"legacy old\"><span>x</span><p><span style=\"b\">y</span></p></div><div><span style=\"c\">z</span></div>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn selector_3() {
+        let source = "<footer id=\"footer\" title=\"f\"><p title=\"a\"><span title=\"b\">x</span></p></footer><p title=\"c\">y</p>";
+        let omits = &["#footer * title"];
+        let expect =
+            "<footer id=\"footer\" title=\"f\"><p><span>x</span></p></footer><p title=\"c\">y</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn selector_4() {
+        let source = "<p class=\"keep\" style=\"a\">x</p><p class=\"other\" style=\"b\">y</p><p lang=\"en-US\" style=\"c\">z</p>";
+        let omits = &["p:not(.keep, [lang|=en]) style"];
+        let expect = "<p class=\"keep\" style=\"a\">x</p><p class=\"other\">y</p><p lang=\"en-US\" style=\"c\">z</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn selector_5() {
+        let source = "<div class=\"legacy\"><span class=\"legacy\">x</span></div>";
+        let omits = &["div class", "div.legacy > span class"];
+        let expect = "<div><span>x</span></div>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn selector_6() {
+        let source = "<a title=\"a b\" href=\"/x\">x</a><a title=\"a\" href=\"/y\">y</a>";
+        let omits = &["a[title=\"a b\"] href", "a[href^='/y'] title"];
+        let expect = "<a title=\"a b\">x</a><a href=\"/y\">y</a>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn invalid_selector_1() {
+        let source = "<span style=\"remove: me;\">Content</span>";
+
+        for omit in ["div[ style", "div:hover style", "div >  style", "a[x=] y"] {
+            let result = try_manipulate(source, &[omit]);
+            assert!(matches!(result, Err(Error::InvalidPattern(_))), "{}", omit);
+        }
+    }
+
+    #[test]
+    fn glob_attr_1() {
+        let source = "<button data-id=\"1\" data-x-y=\"2\" onclick=\"f()\" onmouseover=\"g()\" aria-label=\"b\" class=\"btn\">x</button>";
+        let omits = &["data-*", "on*"];
+        let expect = "<button aria-label=\"b\" class=\"btn\">x</button>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn glob_attr_2() {
+        let source = "<div x-data=\"{}\" v-if=\"a\" :class=\"b\" @click=\"c\" id=\"d\">x</div>";
+        let omits = &["x-*", "v-*", ":*", "@*"];
+        let expect = "<div id=\"d\">x</div>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn glob_tag_1() {
+        let source =
+            "<h1 id=\"a\">x</h1><h2 id=\"b\">y</h2><header id=\"c\">z</header><p id=\"d\">w</p>";
+        let omits = &["h*.id"];
+        let expect = "<h1>x</h1><h2>y</h2><header>z</header><p id=\"d\">w</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn glob_escape_1() {
+        let source = "<div x-on:click.prevent=\"f\" x-on:click=\"g\">x</div>";
+        let omits = &["div.x-on:click\\.prevent"];
+        let expect = "<div x-on:click=\"g\">x</div>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn glob_selector_1() {
+        let source =
+            "<footer id=\"footer\"><p data-a=\"1\" title=\"t\">x</p></footer><p data-b=\"2\">y</p>";
+        let omits = &["#footer *  data-*"];
+        let expect = "<footer id=\"footer\"><p title=\"t\">x</p></footer><p data-b=\"2\">y</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn allow_1() {
+        let source = "<a href=\"/x\" title=\"t\" class=\"c\" onclick=\"f()\">x</a><img src=\"a.png\" alt=\"a\" width=\"1\" height=\"2\" style=\"s\">";
+        let expect = "<a href=\"/x\" title=\"t\">x</a><img src=\"a.png\" alt=\"a\">";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_allow_attrs(&["href", "src", "alt", "title"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn allow_2() {
+        let source = "<a href=\"/x\" title=\"t\" src=\"s\">x</a><img src=\"a.png\" alt=\"a\" width=\"1\" height=\"2\" href=\"h\">";
+        let expect = "<a href=\"/x\" title=\"t\">x</a><img src=\"a.png\" alt=\"a\" width=\"1\" height=\"2\">";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_allow_attrs(&[
+                "a.href",
+                "a.title",
+                "img.src",
+                "img.alt",
+                "img.width",
+                "img.height",
+            ]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn allow_3() {
+        let source =
+            "<a href=\"/x\" target=\"_blank\" data-id=\"1\" data-track=\"2\" class=\"c\">x</a>";
+        let expect = "<a href=\"/x\" data-id=\"1\">x</a>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&["data-track"]).set_allow_attrs(&["href", "data-*"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn allow_4() {
+        let source = "<div class=\"keep\" id=\"a\"><p class=\"x\" id=\"b\">x</p></div>";
+        let expect = "<div class=\"keep\"><p>x</p></div>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_allow_attrs(&["div.keep class"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn allow_5() {
+        let source = "<p class=\"x\" id=\"b\">x</p>";
+        let expect = "<p>x</p>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_allow_attrs(&[]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_allow_attrs(&["a.b.c"]))
+            .run(source);
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn value_equals_1() {
+        let source = "<a href=\"/x\" target=\"_blank\">x</a><a href=\"/y\" target=\"_self\">y</a>";
+        let omits = &["a[target=_blank]"];
+        let expect = "<a href=\"/x\">x</a><a href=\"/y\" target=\"_self\">y</a>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn value_contains_1() {
+        let source =
+            "<p style=\"mso-line-height: 1; color: red\">x</p><p style=\"color: blue\">y</p>";
+        let omits = &["[style*=mso-]"];
+        let expect = "<p>x</p><p style=\"color: blue\">y</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn value_prefix_suffix_1() {
+        let source = "<span class=\"Apple-converted-space\">x</span><span class=\"keep\">y</span><a href=\"/a.pdf\">z</a><a href=\"/b.html\">w</a>";
+        let omits = &["span[class^=Apple-converted-]", "a[href$=.pdf]"];
+        let expect = "<span>x</span><span class=\"keep\">y</span><a>z</a><a href=\"/b.html\">w</a>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn value_includes_dash_1() {
+        let source = "<p class=\"a x\" lang=\"en-US\">x</p><p class=\"ax\" lang=\"english\">y</p>";
+        let omits = &["p[class~=x]", "p[lang|=en]"];
+        let expect = "<p>x</p><p class=\"ax\" lang=\"english\">y</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+
+        // same as condition of selector
+        let omits = &["p[class~=x] class", "p[lang|=en] lang"];
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn value_regex_1() {
+        let source = "<p class=\"MsoNormal\">x</p><p class=\"MsoListParagraph\">y</p><p class=\"normal\">z</p>";
+        let omits = &["p[class=~^Mso(Normal|List.*)$]"];
+        let expect = "<p>x</p><p>y</p><p class=\"normal\">z</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn value_quoted_1() {
+        let source = "<p title=\"a b.c\" lang=\"en\">x</p><p title=\"a\" lang=\"en\">y</p>";
+        let omits = &["p[title=\"a b.c\"]", "p[title=\"a b.c\"] lang"];
+        let expect = "<p>x</p><p title=\"a\" lang=\"en\">y</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn value_selector_1() {
+        let source =
+            "<div class=\"legacy\"><p style=\"mso-x: 1\">x</p></div><p style=\"mso-x: 1\">y</p>";
+        let omits = &["div.legacy p [style*=mso-]"];
+        let expect = "<div class=\"legacy\"><p>x</p></div><p style=\"mso-x: 1\">y</p>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn value_allow_1() {
+        let source = "<a href=\"https://x.domain/\">x</a><a href=\"javascript:f()\">y</a>";
+        let expect = "<a href=\"https://x.domain/\">x</a><a>y</a>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_allow_attrs(&["a[href^=https:]"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn invalid_value_1() {
+        let source = "<p class=\"x\">x</p>";
+
+        for omit in [
+            "[class=~(]",
+            "p[title=\"a b]",
+            "a.target=_blank",
+            "[=x]",
+            "[class x]",
+        ] {
+            let result = try_manipulate(source, &[omit]);
+            assert!(matches!(result, Err(Error::InvalidPattern(_))), "{}", omit);
+        }
+    }
+
+    #[test]
+    fn value_glob_1() {
+        let source = "<div data-a=\"x\" data-b=\"xy\" data-x=\"y\" title=\"x\"></div>";
+        let omits = &["[data-* = x]", "div[data-* *= y]"];
+        let expect = "<div title=\"x\"></div>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn value_glob_2() {
+        let source = "<div data-=\"1\" data-a=\"1\"></div>";
+        // `*` just before `=` is operator of `*=`
+        let omits = &["[data-*=1]"];
+        let expect = "<div data-a=\"1\"></div>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn value_escaped_star_1() {
+        let source = "<div data-*=\"x\" data-a=\"x\"></div>";
+        let omits = &["[data-\\*=x]"];
+        let expect = "<div data-a=\"x\"></div>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn omit_classes_1() {
+        let source = "<span class=\"Apple-converted-space keep\">x</span><p class=\"MsoNormal\">y</p><p class=\"keep  other\">z</p>";
+        let expect = "<span class=\"keep\">x</span><p>y</p><p class=\"keep  other\">z</p>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_omit_classes(&["Apple-converted-space", "Mso*"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn omit_style_properties_1() {
+        let source = "<p style=\"font-family: Arial; color: red; -webkit-text-size-adjust: auto; caret-color: blue;\">x</p><p style=\"FONT-FAMILY: x\">y</p><p style=\"color: red;margin: 0\">z</p>";
+        let expect =
+            "<p style=\"color: red;\">x</p><p>y</p><p style=\"color: red;margin: 0\">z</p>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_omit_style_properties(&[
+                "font-family",
+                "-webkit-*",
+                "caret-color",
+            ]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn omit_style_properties_2() {
+        let source = "<div style=\"background: url(data:image/png;base64,AAA=); font-family: 'a;b'; color: red\">x</div>";
+        let expect =
+            "<div style=\"background: url(data:image/png;base64,AAA=); color: red\">x</div>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&[]).set_omit_style_properties(&["font-family"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn omit_tokens_and_attrs_1() {
+        let source = "<p class=\"MsoNormal a\" style=\"color: red\" id=\"x\">x</p>";
+        let expect = "<p class=\"a\" id=\"x\">x</p>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&["style"]).set_omit_classes(&["Mso*"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn report_1() {
+        let source = "<div onclick=\"f()\"><p>a</p><p onmouseover=\"g()\" title=\"t\">b</p></div>";
+        let expect = "<div><p>a</p><p>b</p></div>";
+
+        let (result, report) = manipulate_with_report(source, &["on*", "p.title", "style"]);
+        assert_eq!(result, expect);
+        assert_eq!(report.fired, vec![2, 1, 0]);
+        assert_eq!(report.not_allowed, 0);
+        assert_eq!(
+            report.omitted,
+            vec![
+                OmittedAttr {
+                    tag: "div".to_owned(),
+                    attr: "onclick".to_owned(),
+                    value: "f()".to_owned(),
+                    path: "html > body > div".to_owned(),
+                    reason: OmitReason::Rule(0),
+                },
+                OmittedAttr {
+                    tag: "p".to_owned(),
+                    attr: "onmouseover".to_owned(),
+                    value: "g()".to_owned(),
+                    path: "html > body > div > p:nth-of-type(2)".to_owned(),
+                    reason: OmitReason::Rule(0),
+                },
+                OmittedAttr {
+                    tag: "p".to_owned(),
+                    attr: "title".to_owned(),
+                    value: "t".to_owned(),
+                    path: "html > body > div > p:nth-of-type(2)".to_owned(),
+                    reason: OmitReason::Rule(1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn report_2() {
+        let source = "<a href=\"/x\" title=\"t\" onclick=\"f()\">x</a>";
+        let expect = "<a href=\"/x\">x</a>";
+
+        let processor = OmitAttr::new(&["title"]).set_allow_attrs(&["title", "href"]);
+        let report = processor.report();
+        let result = Pipeline::new().add_processor(processor).run(source);
+        assert_eq!(result.unwrap(), expect);
+
+        let report = report.take();
+        assert_eq!(report.fired, vec![1]);
+        assert_eq!(report.not_allowed, 1);
+        let omitted = report
+            .omitted
+            .iter()
+            .map(|x| (x.attr.as_str(), x.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            omitted,
+            vec![
+                ("title", OmitReason::Rule(0)),
+                ("onclick", OmitReason::NotAllowed)
+            ]
+        );
+    }
+
+    #[test]
+    fn report_3() {
+        let source = "<p class=\"MsoNormal a\" style=\"font-family: x; color: red;\">x</p>";
+        let expect = "<p class=\"a\" style=\"color: red;\">x</p>";
+
+        let processor = OmitAttr::new(&[])
+            .set_omit_classes(&["Mso*"])
+            .set_omit_style_properties(&["font-family"]);
+        let report = processor.report();
+        let result = Pipeline::new().add_processor(processor).run(source);
+        assert_eq!(result.unwrap(), expect);
+
+        let report = report.take();
+        let omitted = report
+            .omitted
+            .iter()
+            .map(|x| (x.attr.as_str(), x.value.as_str(), x.reason))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            omitted,
+            vec![
+                ("class", "MsoNormal", OmitReason::Tokens),
+                ("style", "font-family: x", OmitReason::Tokens)
+            ]
+        );
+    }
+
+    #[test]
+    fn report_invalid_1() {
+        let result = try_manipulate_with_report("<p>x</p>", &["a.b.c"]);
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn namespace_1() {
+        let source = "<a href=\"/x\">x</a><svg><a xlink:href=\"#a\" href=\"#b\"></a><use xlink:href=\"#c\" xml:lang=\"en\"></use></svg>";
+        let expect =
+            "<a href=\"/x\">x</a><svg><a href=\"#b\"></a><use xml:lang=\"en\"></use></svg>";
+
+        let result = manipulate(source, &["svg|*.xlink:href"]);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn namespace_2() {
+        let source =
+            "<a href=\"/x\">x</a><svg><a href=\"#b\"></a></svg><math><mi href=\"#c\">x</mi></math>";
+        let expect = "<a href=\"/x\">x</a><svg><a></a></svg><math><mi href=\"#c\">x</mi></math>";
+
+        let result = manipulate(source, &["svg|a.href"]);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn namespace_3() {
+        let source = "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\"><use xlink:href=\"#a\" xml:space=\"preserve\"></use></svg>";
+        let expect =
+            "<svg xmlns=\"http://www.w3.org/2000/svg\"><use xlink:href=\"#a\"></use></svg>";
+
+        let result = manipulate(source, &["xmlns:*", "xml:*"]);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn namespace_selector_1() {
+        let source = "<svg><use xlink:href=\"#a\" class=\"x\"></use><use xlink:href=\"https://other.domain/a.svg#b\" class=\"x\"></use></svg>";
+        let expect = "<svg><use xlink:href=\"#a\" class=\"x\"></use><use class=\"x\"></use></svg>";
+
+        let result = manipulate(source, &["svg|use[xlink|href^=http] xlink:href"]);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn namespace_case_1() {
+        let source = "<svg viewBox=\"0 0 10 10\" preserveAspectRatio=\"none\" width=\"10\"><linearGradient gradientUnits=\"userSpaceOnUse\"></linearGradient></svg>";
+        let expect = "<svg viewBox=\"0 0 10 10\" preserveAspectRatio=\"none\"><linearGradient></linearGradient></svg>";
+
+        let result = manipulate(source, &["svg.width", "lineargradient.gradientunits"]);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn invalid_namespace_1() {
+        let source = "<svg></svg>";
+
+        let result = try_manipulate(source, &["foo|*.href"]);
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+        let result = try_manipulate(source, &["foo|use href"]);
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn glob_many_stars_1() {
+        let name = format!("data-{}", "a".repeat(60));
+        let source = format!("<p {}=\"1\" {}b=\"2\">x</p>", name, name);
+        let omit = format!("data-{}b", "*a".repeat(20));
+        let expect = format!("<p {}=\"1\">x</p>", name);
+
+        let result = manipulate(&source, &[omit.as_str()]);
+        assert_eq!(result, expect);
+    }
 }
@@ -1,8 +1,10 @@
-use html5ever::tendril::TendrilSink;
-use html5ever::{parse_document, ParseOpts};
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 
-use crate::core::consts::SELF_CLOSING_TAGS;
+use std::rc::Rc;
+
+use crate::core::preserve::filter_nodes;
+use crate::pipeline::{Pipeline, Processor};
+use crate::{CommentPolicy, Error};
 
 mod tests;
 
@@ -22,77 +24,93 @@ mod tests;
 /// ```
 ///
 pub fn manipulate(html: &str, omit_tags: &[&str]) -> String {
-    let dom = parse_document(RcDom::default(), ParseOpts::default())
-        .from_utf8()
-        .read_from(&mut html.as_bytes())
-        .unwrap();
+    try_manipulate(html, omit_tags).unwrap_or_else(|err| panic!("{}", err))
+}
 
-    let mut omit_tags = Vec::from(omit_tags);
-    // omits document outline tags if necessary
-    if !html.contains("<html>") {
-        omit_tags.push("html")
-    };
-    if !html.contains("<head>") {
-        omit_tags.push("head")
-    };
-    if !html.contains("<body>") {
-        omit_tags.push("body")
-    };
-    let omit_tags = omit_tags.as_slice();
+/// omits specific enclosures of tags in html, returning error instead of panic
+pub fn try_manipulate(html: &str, omit_tags: &[&str]) -> Result<String, Error> {
+    Pipeline::new()
+        .add_processor(OmitEnclosure::new(omit_tags))
+        .run(html)
+}
 
-    let mut output = String::new();
-    scan(&dom.document, omit_tags, &mut output);
+/// `Processor` omitting specific enclosures of tags, for use in `Pipeline`
+pub struct OmitEnclosure {
+    omit_tags: Vec<String>,
+    /// comments handling. Default: keep all
+    comments: CommentPolicy,
+    /// keeps doctype or not. Default: true
+    doctype: bool,
+}
+impl OmitEnclosure {
+    pub fn new(omit_tags: &[&str]) -> Self {
+        OmitEnclosure {
+            omit_tags: omit_tags.iter().map(|x| x.to_string()).collect(),
+            comments: CommentPolicy::Keep,
+            doctype: true,
+        }
+    }
+    pub fn set_comments(mut self, comments: CommentPolicy) -> Self {
+        self.comments = comments;
+        self
+    }
+    pub fn set_doctype(mut self, doctype: bool) -> Self {
+        self.doctype = doctype;
+        self
+    }
+}
+impl Processor for OmitEnclosure {
+    fn process(&self, dom: &mut RcDom) -> Result<(), Error> {
+        let omit_tags = self
+            .omit_tags
+            .iter()
+            .map(|x| x.as_str())
+            .collect::<Vec<&str>>();
 
-    output
+        scan(&dom.document, &omit_tags);
+        filter_nodes(&dom.document, self.comments, self.doctype);
+        Ok(())
+    }
 }
 
 /// scan to manipulate dom recursively
-fn scan(handle: &Handle, omit_tags: &[&str], output: &mut String) {
+///
+/// children of omitted elements are moved up to take their place
+pub(crate) fn scan(handle: &Handle, omit_tags: &[&str]) {
     let node = handle;
 
-    match &node.data {
-        NodeData::Document => {
-            for child in node.children.borrow().iter() {
-                scan(child, omit_tags, output);
-            }
+    if let NodeData::Element {
+        ref template_contents,
+        ..
+    } = node.data
+    {
+        if let Some(contents) = template_contents.borrow().as_ref() {
+            scan(contents, omit_tags);
         }
-        NodeData::Element {
-            ref name,
-            ref attrs,
-            ..
-        } => {
-            let tag_name = name.local.as_ref();
-            if !omit_tags.contains(&tag_name) {
-                // start tag
-                output.push('<');
-                output.push_str(tag_name);
-
-                let attrs = attrs
-                    .clone()
-                    .into_inner()
-                    .iter()
-                    .map(|x| format!(" {}=\"{}\"", x.name.local, x.value))
-                    .collect::<Vec<String>>()
-                    .join("");
-                output.push_str(attrs.as_str());
-
-                output.push('>');
-            }
+    }
 
-            for child in node.children.borrow().iter() {
-                scan(child, omit_tags, output);
-            }
+    let children = node.children.take();
+    let mut manipulated = Vec::with_capacity(children.len());
+    for child in children {
+        scan(&child, omit_tags);
 
-            if !SELF_CLOSING_TAGS.contains(&tag_name) && !omit_tags.contains(&tag_name) {
-                // end tag
-                output.push_str("</");
-                output.push_str(tag_name);
-                output.push('>');
+        match &child.data {
+            NodeData::Element {
+                ref name,
+                ref template_contents,
+                ..
+            } if omit_tags.contains(&name.local.as_ref()) => {
+                let grandchildren = match template_contents.borrow().as_ref() {
+                    Some(contents) => contents.children.take(),
+                    None => child.children.take(),
+                };
+                for grandchild in grandchildren {
+                    grandchild.parent.set(Some(Rc::downgrade(node)));
+                    manipulated.push(grandchild);
+                }
             }
+            _ => manipulated.push(child),
         }
-        NodeData::Text { ref contents } => {
-            output.push_str(&contents.borrow());
-        }
-        _ => {}
     }
+    *node.children.borrow_mut() = manipulated;
 }
@@ -1,7 +1,9 @@
 /// `cargo test`
 #[cfg(test)]
 mod tests {
-    use crate::omit_enclosure::manipulate;
+    use crate::omit_enclosure::{manipulate, try_manipulate, OmitEnclosure};
+    use crate::pipeline::Pipeline;
+    use crate::CommentPolicy;
 
     #[test]
     fn manipulate_1() {
@@ -57,7 +59,7 @@ mod tests {
     fn manipulate_6() {
         let source = r#"<!DOCTYPE html><span style="caret-color: rgb(10, 10, 10); color: rgb(10, 10, 10); font-family: Arial, sans-serif; font-size: 14.036885px; font-style: normal; font-variant-caps: normal; font-weight: 400; letter-spacing: normal; orphans: auto; text-align: left; text-indent: 0px; text-transform: none; white-space: normal; widows: auto; word-spacing: 0px; -webkit-tap-highlight-color: rgba(0, 0, 0, 0.4); -webkit-text-stroke-width: 0px; background-color: rgb(254, 254, 254); text-decoration: none; display: inline !important; float: none;">To avoid polluting the<span class="Apple-converted-space"> </span></span><a href="https://github.com/" title="Click to open https://github.com/" style="font-family: Arial, sans-serif; font-size: 14.036885px; font-style: normal; font-variant-caps: normal; letter-spacing: normal; orphans: auto; text-align: left; text-indent: 0px; text-transform: none; white-space: normal; widows: auto; word-spacing: 0px; -webkit-tap-highlight-color: rgba(0, 0, 0, 0.4); -webkit-text-stroke-width: 0px; text-decoration: none; font-weight: bold; color: rgb(0, 102, 153);">some text<span class="Apple-converted-space"> </span></a><span style="caret-color: rgb(10, 10, 10); color: rgb(10, 10, 10); font-family: Arial, sans-serif; font-size: 14.036885px; font-style: normal; font-variant-caps: normal; font-weight: 400; letter-spacing: normal; orphans: auto; text-align: left; text-indent: 0px; text-transform: none; white-space: normal; widows: auto; word-spacing: 0px; -webkit-tap-highlight-color: rgba(0, 0, 0, 0.4); -webkit-text-stroke-width: 0px; background-color: rgb(254, 254, 254); text-decoration: none; display: inline !important; float: none;"></span><span style="caret-color: rgb(10, 10, 10); color: rgb(10, 10, 10); font-family: Arial, sans-serif; font-size: 14.036885px; font-style: normal; font-variant-caps: normal; font-weight: 400; letter-spacing: normal; orphans: auto; text-align: left; text-indent: 0px; text-transform: none; white-space: normal; widows: auto; word-spacing: 0px; -webkit-tap-highlight-color: rgba(0, 0, 0, 0.4); -webkit-text-stroke-width: 0px; text-decoration: none;">#50105</span><span style="caret-color: rgb(10, 10, 10); color: rgb(10, 10, 10); font-family: Arial, sans-serif; font-size: 14.036885px; font-style: normal; font-variant-caps: normal; font-weight: 400; letter-spacing: normal; orphans: auto; text-align: left; text-indent: 0px; text-transform: none; white-space: normal; widows: auto; word-spacing: 0px; -webkit-tap-highlight-color: rgba(0, 0, 0, 0.4); -webkit-text-stroke-width: 0px; background-color: rgb(254, 254, 254); text-decoration: none; display: inline !important; float: none;"><span class="Apple-converted-space"> </span>trailing text</span>"#;
         let omits = &["span"];
-        let expect = r#"To avoid polluting the <a href="https://github.com/" title="Click to open https://github.com/" style="font-family: Arial, sans-serif; font-size: 14.036885px; font-style: normal; font-variant-caps: normal; letter-spacing: normal; orphans: auto; text-align: left; text-indent: 0px; text-transform: none; white-space: normal; widows: auto; word-spacing: 0px; -webkit-tap-highlight-color: rgba(0, 0, 0, 0.4); -webkit-text-stroke-width: 0px; text-decoration: none; font-weight: bold; color: rgb(0, 102, 153);">some text </a>#50105 trailing text"#;
+        let expect = r#"<!DOCTYPE html>To avoid polluting the&nbsp;<a href="https://github.com/" title="Click to open https://github.com/" style="font-family: Arial, sans-serif; font-size: 14.036885px; font-style: normal; font-variant-caps: normal; letter-spacing: normal; orphans: auto; text-align: left; text-indent: 0px; text-transform: none; white-space: normal; widows: auto; word-spacing: 0px; -webkit-tap-highlight-color: rgba(0, 0, 0, 0.4); -webkit-text-stroke-width: 0px; text-decoration: none; font-weight: bold; color: rgb(0, 102, 153);">some text&nbsp;</a>#50105&nbsp;trailing text"#;
 
         let result = manipulate(source, omits);
         assert_eq!(result, expect);
@@ -115,4 +117,50 @@ mod tests {
         let result = manipulate(source, omits);
         assert_eq!(result, expect);
     }
+
+    #[test]
+    fn try_manipulate_1() {
+        let source = "<div><span>Remove me</span><p>Keep me</p></div>";
+        let omits = &["span"];
+        let expect = "<div>Remove me<p>Keep me</p></div>";
+
+        let result = try_manipulate(source, omits);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn html_outline_4() {
+        let source = "<html lang=\"en\"><BODY class=\"page\"><div><span>Remove me</span></div></BODY></html>";
+        let omits = &["span"];
+        let expect = "<html lang=\"en\"><body class=\"page\"><div>Remove me</div></body></html>";
+
+        let result = manipulate(source, omits);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn preserve_1() {
+        let source = "<!DOCTYPE html><!--! license --><!-- note --><!--[if !IE]><!--><span>Remove me</span><!--<![endif]-->";
+        let expect = "<!--! license --><!--[if !IE]><!-->Remove me<!--<![endif]-->";
+
+        let result = Pipeline::new()
+            .add_processor(
+                OmitEnclosure::new(&["span"])
+                    .set_comments(CommentPolicy::KeepConditionalAndLicense)
+                    .set_doctype(false),
+            )
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn preserve_2() {
+        let source = "<template><!-- note --><span>Remove me</span></template>";
+        let expect = "<template>Remove me</template>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitEnclosure::new(&["span"]).set_comments(CommentPolicy::Strip))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
 }
@@ -1,60 +1,206 @@
-use html5ever::tendril::TendrilSink;
-use html5ever::{parse_document, ParseOpts};
+use html5ever::Attribute;
 use markup5ever_rcdom::{Handle, NodeData, RcDom};
 
-use std::path::Path;
+use std::cell::RefCell;
+use std::net::Ipv6Addr;
 
-use crate::core::utils::reset_document_outline;
+use crate::core::preserve::filter_nodes;
+use crate::core::url::{resolve, scheme, split_origin, validate_url_options};
+use crate::core::utils::remove_node;
+use crate::pipeline::{InputMode, Pipeline, Processor};
+use crate::{CommentPolicy, Error};
 
 mod tests;
+mod visit;
+
+pub use visit::UrlKind;
+pub(crate) use visit::{visit_urls, UrlRef, UrlTargets};
 
 /// URL HTTP protocol
+#[derive(Clone)]
 pub enum HttpProtocol {
     Http,
     Https,
 }
+/// what to do with `<base>` element after conversion
+#[derive(Clone, Copy, Default)]
+pub enum BaseElement {
+    #[default]
+    Keep,
+    Remove,
+    /// rewrites `href` into absolute url
+    Absolutize,
+}
 /// conversion target tag
+#[derive(Clone)]
 pub enum ConvertTag {
     A,
     Img,
     Audio,
+    /// `src` and `poster`
     Video,
     Source,
     Link,
     Script,
+    /// `img` and `source` in `picture`
+    Picture,
+    /// `url()` and `@import` in `style` attributes of all tags and `style` elements
+    Style,
+    Form,
+    Button,
+    /// `formaction`, and `src` of `type="image"`
+    Input,
+    Iframe,
+    Embed,
+    Object,
+    Track,
+    Area,
+    /// `cite` of `blockquote`, `q`, `del` and `ins`
+    Cite,
+    /// `content` of `http-equiv="refresh"`
+    Meta,
+    /// `href` and `xlink:href` of SVG `use` and `image`
+    Svg,
+}
+impl ConvertTag {
+    /// all conversion target tags to fully absolutize document
+    pub fn all() -> Vec<ConvertTag> {
+        Vec::from_iter([
+            ConvertTag::A,
+            ConvertTag::Img,
+            ConvertTag::Audio,
+            ConvertTag::Video,
+            ConvertTag::Source,
+            ConvertTag::Link,
+            ConvertTag::Script,
+            ConvertTag::Picture,
+            ConvertTag::Style,
+            ConvertTag::Form,
+            ConvertTag::Button,
+            ConvertTag::Input,
+            ConvertTag::Iframe,
+            ConvertTag::Embed,
+            ConvertTag::Object,
+            ConvertTag::Track,
+            ConvertTag::Area,
+            ConvertTag::Cite,
+            ConvertTag::Meta,
+            ConvertTag::Svg,
+        ])
+    }
+    /// tag names in html
+    pub(crate) fn tag_names(&self) -> &'static [&'static str] {
+        match self {
+            ConvertTag::A => &["a"],
+            ConvertTag::Img => &["img"],
+            ConvertTag::Audio => &["audio"],
+            ConvertTag::Video => &["video"],
+            ConvertTag::Source => &["source"],
+            ConvertTag::Link => &["link"],
+            ConvertTag::Script => &["script"],
+            ConvertTag::Picture => &["picture"],
+            ConvertTag::Style => &["style"],
+            ConvertTag::Form => &["form"],
+            ConvertTag::Button => &["button"],
+            ConvertTag::Input => &["input"],
+            ConvertTag::Iframe => &["iframe"],
+            ConvertTag::Embed => &["embed"],
+            ConvertTag::Object => &["object"],
+            ConvertTag::Track => &["track"],
+            ConvertTag::Area => &["area"],
+            ConvertTag::Cite => &["blockquote", "q", "del", "ins"],
+            ConvertTag::Meta => &["meta"],
+            ConvertTag::Svg => &["use", "image"],
+        }
+    }
 }
 // conversion target attr of each tag
-const CONVERT_TAG_ATTRS: [(&str, &str); 7] = [
+// (`href` of SVG tags matches `xlink:href` too)
+const CONVERT_TAG_ATTRS: [(&str, &str); 23] = [
     ("a", "href"),
     ("img", "src"),
     ("audio", "src"),
     ("video", "src"),
+    ("video", "poster"),
     ("source", "src"),
     ("link", "href"),
     ("script", "src"),
+    ("form", "action"),
+    ("button", "formaction"),
+    ("input", "formaction"),
+    ("input", "src"),
+    ("iframe", "src"),
+    ("embed", "src"),
+    ("object", "data"),
+    ("track", "src"),
+    ("area", "href"),
+    ("blockquote", "cite"),
+    ("q", "cite"),
+    ("del", "cite"),
+    ("ins", "cite"),
+    ("use", "href"),
+    ("image", "href"),
+];
+// conversion target attr of each tag whose value is srcset candidate list
+const CONVERT_TAG_SRCSET_ATTRS: [(&str, &str); 3] = [
+    ("img", "srcset"),
+    ("source", "srcset"),
+    ("link", "imagesrcset"),
 ];
+// tags in `picture` converted by `ConvertTag::Picture`
+const PICTURE_TAGS: [&str; 2] = ["img", "source"];
 
 /// conversion options
+#[derive(Clone)]
 pub struct ConvertOptions {
     /// HTTP protocol
     http_protocol: HttpProtocol,
     /// FQDN (host name, domain)
     fqdn: String,
-    /// port number. Default: 443 on HTTPS, 80 on HTTP
-    port: u16,
+    /// port number. Default (`None`): 443 on HTTPS, 80 on HTTP
+    port: Option<u16>,
     /// path base. Default: /
+    /// treated as directory only when it ends with `/` (`/docs/page` resolves `x` to `/docs/x`)
     current_path: String,
     /// target tags
     tags: Vec<ConvertTag>,
+    /// additional target attr of each tag, converted regardless of `tags`
+    tag_attrs: Vec<(String, String)>,
+    /// schemes left untouched besides RFC 3986 ones, such as `my_app` having invalid character.
+    /// Default: none
+    pass_through_schemes: Vec<String>,
+    /// leaves in-page links such as `#section` untouched or not. Default: true
+    keep_fragment_links: bool,
+    /// converts srcset candidates or not. Default: true
+    srcset: bool,
+    /// resolves against `<base href>` in document instead of `current_path` or not. Default: true
+    respect_base: bool,
+    /// `<base>` element handling after conversion. Default: keep
+    base_element: BaseElement,
+    /// comments handling. Default: keep all
+    comments: CommentPolicy,
+    /// keeps doctype or not. Default: true
+    doctype: bool,
+    /// how to parse input html. Default: `InputMode::Auto`
+    input_mode: InputMode<'static>,
 }
 impl ConvertOptions {
     pub fn new(fqdn: &str) -> Self {
         ConvertOptions {
             http_protocol: HttpProtocol::Https,
             fqdn: fqdn.to_owned(),
-            port: 443 as u16,
+            port: None,
             current_path: String::from("/"),
             tags: Vec::from_iter([ConvertTag::A]),
+            tag_attrs: Vec::new(),
+            pass_through_schemes: Vec::new(),
+            keep_fragment_links: true,
+            srcset: true,
+            respect_base: true,
+            base_element: BaseElement::Keep,
+            comments: CommentPolicy::Keep,
+            doctype: true,
+            input_mode: InputMode::Auto,
         }
     }
     pub fn set_http_protocol(mut self, http_protocol: HttpProtocol) -> Self {
@@ -65,8 +211,9 @@ impl ConvertOptions {
         self.fqdn = fqdn.to_owned();
         self
     }
+    /// default port of protocol is omitted in urls
     pub fn set_port(mut self, port: u16) -> Self {
-        self.port = port;
+        self.port = Some(port);
         self
     }
     pub fn set_current_path(mut self, current_path: &str) -> Self {
@@ -77,13 +224,164 @@ impl ConvertOptions {
         self.tags = tags;
         self
     }
+    /// adds conversion target attr of tag such as `("div", "data-href")`
+    pub fn add_tag_attr(mut self, tag: &str, attr: &str) -> Self {
+        self.tag_attrs.push((tag.to_owned(), attr.to_owned()));
+        self
+    }
+    /// sets non-standard schemes whose urls are left untouched, such as `my_app` in `my_app:open`.
+    ///
+    /// urls with any valid scheme (RFC 3986) such as `mailto:` or `ssh:` and network-path
+    /// references such as `//cdn.domain/x` are always left untouched, so listing such schemes
+    /// has no effect, and paths looking like them such as `a:b` are never converted
+    /// (write `./a:b` instead).
+    pub fn set_pass_through_schemes(mut self, pass_through_schemes: &[&str]) -> Self {
+        self.pass_through_schemes = pass_through_schemes
+            .iter()
+            .map(|x| x.to_ascii_lowercase())
+            .collect();
+        self
+    }
+    /// `false` makes `#section` absolute such as `https://some.domain/current/path#section`
+    pub fn set_keep_fragment_links(mut self, keep_fragment_links: bool) -> Self {
+        self.keep_fragment_links = keep_fragment_links;
+        self
+    }
+    /// `srcset` of `img` and `source`, and `imagesrcset` of `link`
+    pub fn set_srcset(mut self, srcset: bool) -> Self {
+        self.srcset = srcset;
+        self
+    }
+    /// `false` ignores `<base href>` and always resolves against `current_path`
+    pub fn set_respect_base(mut self, respect_base: bool) -> Self {
+        self.respect_base = respect_base;
+        self
+    }
+    pub fn set_base_element(mut self, base_element: BaseElement) -> Self {
+        self.base_element = base_element;
+        self
+    }
+    pub fn set_comments(mut self, comments: CommentPolicy) -> Self {
+        self.comments = comments;
+        self
+    }
+    pub fn set_doctype(mut self, doctype: bool) -> Self {
+        self.doctype = doctype;
+        self
+    }
+    /// sets how to parse input html. Default: `InputMode::Auto`
+    pub fn set_input_mode(mut self, input_mode: InputMode<'static>) -> Self {
+        self.input_mode = input_mode;
+        self
+    }
+
+    pub(crate) fn input_mode(&self) -> InputMode<'static> {
+        self.input_mode
+    }
+    /// internal options resolving against `url_prefix` and `current_path`
+    pub(crate) fn actual<'a>(
+        &'a self,
+        url_prefix: &'a str,
+        current_path: &'a str,
+    ) -> ActualConvertOptions<'a> {
+        ActualConvertOptions {
+            url_prefix,
+            current_path,
+            pass_through_schemes: &self.pass_through_schemes,
+            keep_fragment_links: self.keep_fragment_links,
+        }
+    }
+    /// targets of conversion
+    pub(crate) fn url_targets(&self) -> UrlTargets<'_> {
+        UrlTargets::new(&self.tags, &self.tag_attrs, self.srcset)
+    }
+
+    /// url prefix and path base, taking `<base href>` into account
+    pub(crate) fn url_base(&self, base_href: Option<&str>) -> (String, String) {
+        let url_prefix = self.url_prefix();
+        let base_href = match base_href {
+            Some(x) if self.respect_base => x.trim(),
+            _ => return (url_prefix, self.current_path.to_owned()),
+        };
+
+        let is_http = |x: &str| x.eq_ignore_ascii_case("http") || x.eq_ignore_ascii_case("https");
+        match (scheme(base_href), split_origin(base_href)) {
+            // other origin
+            (Some(scheme), Some((origin, path))) if is_http(scheme) => {
+                let path = resolve("/", path);
+                (origin.to_owned(), path)
+            }
+            // network-path reference
+            (None, Some((origin, path))) => {
+                let scheme = url_prefix.split("//").next().unwrap_or_default();
+                let path = resolve("/", path);
+                (format!("{}{}", scheme, origin), path)
+            }
+            // unsupported scheme such as `javascript:`
+            (Some(_), _) => (url_prefix, self.current_path.to_owned()),
+            _ => {
+                let path = resolve(&self.current_path, base_href);
+                (url_prefix, path)
+            }
+        }
+    }
+
+    /// scheme, host and port such as `https://some.domain:8443`
+    pub(crate) fn url_prefix(&self) -> String {
+        let (scheme, default_port) = match self.http_protocol {
+            HttpProtocol::Http => ("http", 80),
+            HttpProtocol::Https => ("https", 443),
+        };
+        // IPv6 literal
+        let host = if self.fqdn.parse::<Ipv6Addr>().is_ok() {
+            format!("[{}]", self.fqdn)
+        } else {
+            self.fqdn.to_owned()
+        };
+        match self.port {
+            Some(port) if port != default_port => format!("{}://{}:{}", scheme, host, port),
+            _ => format!("{}://{}", scheme, host),
+        }
+    }
 }
 
 /// internal options made of specified options
-struct ActualConvertOptions<'a> {
-    url_prefix: &'a str,
-    current_path: &'a str,
-    tags: &'a Vec<&'a str>,
+pub(crate) struct ActualConvertOptions<'a> {
+    pub(crate) url_prefix: &'a str,
+    pub(crate) current_path: &'a str,
+    pass_through_schemes: &'a [String],
+    keep_fragment_links: bool,
+}
+impl ActualConvertOptions<'_> {
+    /// checks if trimmed attr value is path to convert
+    pub(crate) fn is_path(&self, value: &str) -> bool {
+        if value.starts_with("//") {
+            return false;
+        }
+        if value.starts_with('#') {
+            return !self.keep_fragment_links;
+        }
+        if scheme(value).is_some() {
+            return false;
+        }
+        !self.pass_through_schemes.iter().any(|x| {
+            value
+                .get(..x.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(x))
+                && value[x.len()..].starts_with(':')
+        })
+    }
+    /// converts path to url. returns `None` when value is not path
+    fn convert(&self, value: &str) -> Option<String> {
+        // browsers strip leading and trailing whitespace
+        let value = value.trim();
+        if !self.is_path(value) {
+            return None;
+        }
+        // absolute or relative path
+        let path = resolve(self.current_path, value);
+        Some(format!("{}{}", self.url_prefix, path))
+    }
 }
 
 /// convert paths in html to urls
@@ -102,114 +400,134 @@ struct ActualConvertOptions<'a> {
 /// assert_eq!(result, expect);
 /// ```
 ///
+/// `options` are not validated as in earlier versions, so `fqdn` such as `some.domain:8080`
+/// is used as it is, and `current_path` without leading `/` is treated as starting with `/`.
+/// use `try_convert` to validate them.
 pub fn convert(html: &str, options: &ConvertOptions) -> String {
-    let dom = parse_document(RcDom::default(), ParseOpts::default())
-        .from_utf8()
-        .read_from(&mut html.as_bytes())
-        .unwrap();
-
-    let url_prefix = match (&options.http_protocol, options.port) {
-        (HttpProtocol::Https, 443) => format!("https://{}", options.fqdn),
-        (HttpProtocol::Http, 80) => format!("http://{}", options.fqdn),
-        _ => format!(
-            "{}://{}",
-            match &options.http_protocol {
-                HttpProtocol::Http => "http",
-                _ => "https",
-            },
-            options.fqdn
-        ),
-    };
-    let mut tags = Vec::<&str>::new();
-    options.tags.iter().for_each(|x| match x {
-        ConvertTag::A => tags.push("a"),
-        ConvertTag::Img => tags.push("img"),
-        ConvertTag::Audio => tags.push("audio"),
-        ConvertTag::Video => tags.push("video"),
-        ConvertTag::Source => tags.push("source"),
-        ConvertTag::Link => tags.push("link"),
-        ConvertTag::Script => tags.push("script"),
-    });
-    let actual_option = ActualConvertOptions {
-        url_prefix: &url_prefix,
-        current_path: &options.current_path,
-        tags: &tags,
+    let mut options = options.clone();
+    if !options.current_path.starts_with('/') {
+        options.current_path.insert(0, '/');
+    }
+    let input_mode = options.input_mode;
+    let processor = PathToUrl {
+        options,
+        validate: false,
     };
+    Pipeline::new()
+        .set_input_mode(input_mode)
+        .add_processor(processor)
+        .run(html)
+        .unwrap_or_else(|err| panic!("{}", err))
+}
 
-    let mut output = String::new();
-    scan(&dom.document, &actual_option, &mut output);
+/// convert paths in html to urls, returning error instead of panic
+///
+/// ```rust
+/// use htmlproc::path_to_url::{try_convert, ConvertOptions};
+/// use htmlproc::Error;
+///
+/// let source: &str = "<a href=\"/somewhere\">link</a>";
+///
+/// let result = try_convert(source, &ConvertOptions::new("some.domain"));
+/// assert_eq!(result.unwrap(), "<a href=\"https://some.domain/somewhere\">link</a>");
+///
+/// let result = try_convert(source, &ConvertOptions::new("https://some.domain"));
+/// assert!(matches!(result, Err(Error::InvalidUrlOption(_))));
+/// ```
+///
+pub fn try_convert(html: &str, options: &ConvertOptions) -> Result<String, Error> {
+    Pipeline::new()
+        .set_input_mode(options.input_mode)
+        .add_processor(PathToUrl::new(options.clone()))
+        .run(html)
+}
 
-    reset_document_outline(output.as_str(), html)
+/// `Processor` converting paths to urls, for use in `Pipeline`
+pub struct PathToUrl {
+    options: ConvertOptions,
+    /// `false` on legacy `convert`
+    validate: bool,
+}
+impl PathToUrl {
+    pub fn new(options: ConvertOptions) -> Self {
+        PathToUrl {
+            options,
+            validate: true,
+        }
+    }
 }
+impl Processor for PathToUrl {
+    fn process(&self, dom: &mut RcDom) -> Result<(), Error> {
+        let options = &self.options;
+        if self.validate {
+            validate(options)?;
+        }
+
+        let base = find_base(&dom.document);
+        let base_href = base.as_ref().and_then(|x| element_attr_value(x, "href"));
+        let (url_prefix, current_path) = options.url_base(base_href.as_deref());
+        let actual_option = options.actual(&url_prefix, &current_path);
 
-/// scan to convert recursively
-fn scan(handle: &Handle, options: &ActualConvertOptions, output: &mut String) {
-    let node = handle;
+        let targets = options.url_targets();
+        visit_urls(&dom.document, &targets, &mut |x| {
+            actual_option.convert(x.url)
+        });
 
-    match &node.data {
-        NodeData::Document => {
-            for child in node.children.borrow().iter() {
-                scan(child, options, output);
+        if let Some(base) = base {
+            match options.base_element {
+                BaseElement::Keep => {}
+                BaseElement::Remove => remove_node(&base),
+                BaseElement::Absolutize => {
+                    if let NodeData::Element { ref attrs, .. } = base.data {
+                        for attr in attrs.borrow_mut().iter_mut() {
+                            if attr.name.local.as_ref() == "href" {
+                                attr.value = format!("{}{}", url_prefix, current_path).into();
+                            }
+                        }
+                    }
+                }
             }
         }
-        NodeData::Element {
-            ref name,
-            ref attrs,
-            ..
-        } => {
-            let tag_name = name.local.as_ref();
-
-            let is_convert_tag = options.tags.contains(&tag_name);
-
-            // start tag
-            output.push('<');
-            output.push_str(tag_name);
-
-            let attrs = attrs
-                .clone()
-                .into_inner()
-                .iter()
-                // tag attrs
-                .map(|x| {
-                    let attr_name = x.name.local.to_string();
-                    let mut attr_value = x.value.to_string();
-                    // path conversion
-                    if is_convert_tag
-                        && CONVERT_TAG_ATTRS.contains(&(tag_name, attr_name.as_str()))
-                        && !attr_value.contains("//")
-                    {
-                        // absolute path
-                        if attr_value.starts_with('/') {
-                            attr_value = format!("{}{}", options.url_prefix, attr_value);
-                        // relative path
-                        } else {
-                            let base_path = Path::new(options.current_path);
-                            let source_path = Path::new(&attr_value);
-                            let joined = base_path.join(source_path);
-                            let path = joined.display();
-                            attr_value = format!("{}{}", options.url_prefix, path);
-                        }
-                    };
-                    format!(" {}=\"{}\"", attr_name, attr_value)
-                })
-                .collect::<Vec<_>>()
-                .join("");
-            output.push_str(attrs.as_str());
 
-            output.push('>');
+        filter_nodes(&dom.document, options.comments, options.doctype);
+        Ok(())
+    }
+}
 
-            for child in node.children.borrow().iter() {
-                scan(child, options, output);
+/// first `base` element with `href` in tree order
+pub(crate) fn find_base(handle: &Handle) -> Option<Handle> {
+    for child in handle.children.borrow().iter() {
+        if let NodeData::Element { ref name, .. } = child.data {
+            if name.local.as_ref() == "base" && element_attr_value(child, "href").is_some() {
+                return Some(child.clone());
             }
-
-            // end tag
-            output.push_str("</");
-            output.push_str(tag_name);
-            output.push('>');
         }
-        NodeData::Text { ref contents } => {
-            output.push_str(&contents.borrow());
+        if let Some(base) = find_base(child) {
+            return Some(base);
         }
-        _ => {}
     }
+    None
+}
+
+/// value of attr of element by name
+pub(crate) fn element_attr_value(handle: &Handle, attr_name: &str) -> Option<String> {
+    match handle.data {
+        NodeData::Element { ref attrs, .. } => attr_value(attrs, attr_name),
+        _ => None,
+    }
+}
+
+/// value of attr by name
+fn attr_value(attrs: &RefCell<Vec<Attribute>>, attr_name: &str) -> Option<String> {
+    attrs
+        .borrow()
+        .iter()
+        .find(|x| x.name.local.as_ref() == attr_name)
+        .map(|x| x.value.to_string())
+}
+
+/// validates specified options
+pub(crate) fn validate(options: &ConvertOptions) -> Result<(), Error> {
+    let ports = options.port.into_iter().collect::<Vec<u16>>();
+    validate_url_options(&[options.fqdn.as_str()], &ports, &options.current_path)
 }
@@ -1,7 +1,11 @@
 /// `cargo test --features path_to_url`
 #[cfg(test)]
 mod tests {
-    use crate::path_to_url::{convert, ConvertOptions, ConvertTag};
+    use crate::path_to_url::{
+        convert, try_convert, BaseElement, ConvertOptions, ConvertTag, HttpProtocol,
+    };
+    use crate::pipeline::InputMode;
+    use crate::{CommentPolicy, Error};
 
     #[test]
     fn convert_a_1() {
@@ -30,7 +34,7 @@ mod tests {
         let source = "<a href=\"../somewhere\">link</a>";
         let fqdn = "some.domain";
         let options = ConvertOptions::new(fqdn).set_current_path("/some/where");
-        let expect = "<a href=\"https://some.domain/some/where/../somewhere\">link</a>";
+        let expect = "<a href=\"https://some.domain/somewhere\">link</a>";
 
         let result = convert(source, &options);
         assert_eq!(result, expect);
@@ -148,4 +152,536 @@ mod tests {
         let result = convert(source, &options);
         assert_eq!(result, expect);
     }
+
+    #[test]
+    fn escaped_1() {
+        let source = "<a href=\"/search?q=a&amp;b=&quot;c&quot;\">a &amp; b</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn);
+        let expect = "<a href=\"https://some.domain/search?q=a&amp;b=&quot;c&quot;\">a &amp; b</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn try_convert_1() {
+        let source = "<a href=\"/somewhere\">link</a>";
+        let options = ConvertOptions::new("some.domain");
+        let expect = "<a href=\"https://some.domain/somewhere\">link</a>";
+
+        let result = try_convert(source, &options);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn invalid_option_1() {
+        let source = "<a href=\"/somewhere\">link</a>";
+
+        for options in [
+            ConvertOptions::new(""),
+            ConvertOptions::new("https://some.domain"),
+            ConvertOptions::new("some.domain").set_port(0),
+            ConvertOptions::new("some.domain").set_current_path("some/where"),
+        ] {
+            let result = try_convert(source, &options);
+            assert!(matches!(result, Err(Error::InvalidUrlOption(_))));
+        }
+    }
+
+    #[test]
+    fn legacy_option_1() {
+        let source = "<a href=\"/somewhere\">link</a><a href=\"page\">link</a>";
+        let options = ConvertOptions::new("some.domain:8080").set_current_path("some/where/");
+        let expect = "<a href=\"https://some.domain:8080/somewhere\">link</a><a href=\"https://some.domain:8080/some/where/page\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn preserve_1() {
+        let source = "<!DOCTYPE html><!-- note --><a href=\"/somewhere\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn);
+        let expect =
+            "<!DOCTYPE html><!-- note --><a href=\"https://some.domain/somewhere\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn preserve_2() {
+        let source = "<!DOCTYPE html><!-- note --><a href=\"/somewhere\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_comments(CommentPolicy::Strip)
+            .set_doctype(false);
+        let expect = "<a href=\"https://some.domain/somewhere\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn input_mode_1() {
+        let source = "<a href=\"/somewhere\">link</a>";
+        let options = ConvertOptions::new("some.domain").set_input_mode(InputMode::Document);
+        let expect = "<html><head></head><body><a href=\"https://some.domain/somewhere\">link</a></body></html>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn void_elements_1() {
+        let source = "<video src=\"/some.file\"><track src=\"/some.vtt\"></video><object><param name=\"a\" value=\"b\"></object>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_tags(Vec::from_iter([ConvertTag::Video]));
+        let expect = "<video src=\"https://some.domain/some.file\"><track src=\"/some.vtt\"></video><object><param name=\"a\" value=\"b\"></object>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn port_1() {
+        let source = "<a href=\"/somewhere\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_port(8443);
+        let expect = "<a href=\"https://some.domain:8443/somewhere\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn port_2() {
+        let source = "<a href=\"/somewhere\">link</a>";
+        let fqdn = "localhost";
+        let options = ConvertOptions::new(fqdn)
+            .set_http_protocol(HttpProtocol::Http)
+            .set_port(8080);
+        let expect = "<a href=\"http://localhost:8080/somewhere\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn port_3() {
+        // default port of each protocol is omitted
+        let source = "<a href=\"/somewhere\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_http_protocol(HttpProtocol::Http);
+        let expect = "<a href=\"http://some.domain/somewhere\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+
+        let options = ConvertOptions::new(fqdn)
+            .set_port(80)
+            .set_http_protocol(HttpProtocol::Http);
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+
+        let options = ConvertOptions::new(fqdn)
+            .set_port(443)
+            .set_http_protocol(HttpProtocol::Http);
+        let expect = "<a href=\"http://some.domain:443/somewhere\">link</a>";
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn ipv6_1() {
+        let source = "<a href=\"/somewhere\">link</a>";
+        let expect = "<a href=\"https://[::1]:8443/somewhere\">link</a>";
+
+        for fqdn in ["::1", "[::1]"] {
+            let options = ConvertOptions::new(fqdn).set_port(8443);
+            let result = convert(source, &options);
+            assert_eq!(result, expect);
+        }
+    }
+
+    #[test]
+    fn invalid_option_2() {
+        let source = "<a href=\"/somewhere\">link</a>";
+        let options = ConvertOptions::new("some.domain:8080");
+
+        let result = try_convert(source, &options);
+        assert!(matches!(result, Err(Error::InvalidUrlOption(_))));
+    }
+
+    #[test]
+    fn convert_a_4() {
+        let source = "<a href=\"../somewhere\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_current_path("/some/where/");
+        let expect = "<a href=\"https://some.domain/some/somewhere\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn resolve_dot_segments_1() {
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_current_path("/a/b/c/d");
+        for (path, expect) in [
+            ("g", "/a/b/c/g"),
+            ("./g", "/a/b/c/g"),
+            ("g/", "/a/b/c/g/"),
+            ("/g", "/g"),
+            (".", "/a/b/c/"),
+            ("./", "/a/b/c/"),
+            ("..", "/a/b/"),
+            ("../g", "/a/b/g"),
+            ("../..", "/a/"),
+            ("../../g", "/a/g"),
+            ("../../../../g", "/g"),
+            ("/./g", "/g"),
+            ("/../g", "/g"),
+            ("g.", "/a/b/c/g."),
+            ("..g", "/a/b/c/..g"),
+            ("./../g", "/a/b/g"),
+            ("g/./h", "/a/b/c/g/h"),
+            ("g/../h", "/a/b/c/h"),
+        ] {
+            let source = format!("<a href=\"{}\">link</a>", path);
+            let expect = format!("<a href=\"https://some.domain{}\">link</a>", expect);
+
+            let result = convert(&source, &options);
+            assert_eq!(result, expect);
+        }
+    }
+
+    #[test]
+    fn resolve_query_fragment_1() {
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_current_path("/docs/page?lang=en");
+        for (path, expect) in [
+            ("../x?a=1#top", "/x?a=1#top"),
+            ("y/../z#sec", "/docs/z#sec"),
+            ("?page=2", "/docs/page?page=2"),
+            ("", "/docs/page?lang=en"),
+        ] {
+            let source = format!("<a href=\"{}\">link</a>", path);
+            let expect = format!("<a href=\"https://some.domain{}\">link</a>", expect);
+
+            let result = convert(&source, &options);
+            assert_eq!(result, expect);
+        }
+    }
+
+    #[test]
+    fn skipped_scheme_1() {
+        let fqdn = "some.domain";
+        let options =
+            ConvertOptions::new(fqdn).set_tags(Vec::from_iter([ConvertTag::A, ConvertTag::Img]));
+        for source in [
+            "<a href=\"mailto:x@y\">link</a>",
+            "<a href=\"tel:+1\">link</a>",
+            "<a href=\"javascript:void(0)\">link</a>",
+            "<a href=\"HTTPS://other.domain/x\">link</a>",
+            "<a href=\"//cdn.domain/x\">link</a>",
+            "<img src=\"data:image/png;base64,iVBORw0KGgo=\">",
+        ] {
+            let result = convert(source, &options);
+            assert_eq!(result, source);
+        }
+    }
+
+    #[test]
+    fn pass_through_schemes_1() {
+        let source = "<a href=\"my_app:open\">link</a><a href=\"mailto:x@y\">link</a><a href=\"other_app:open\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_pass_through_schemes(&["my_app"]);
+        let expect = "<a href=\"my_app:open\">link</a><a href=\"mailto:x@y\">link</a><a href=\"https://some.domain/other_app:open\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn pass_through_schemes_2() {
+        let source = "<a href=\"ssh://host/x\">link</a><a href=\"git://host/x\">link</a><a href=\"sip:me@x\">link</a><a href=\"vbscript:foo\">link</a><a href=\"x/a:b\">link</a>";
+        let fqdn = "some.domain";
+        let expect = "<a href=\"ssh://host/x\">link</a><a href=\"git://host/x\">link</a><a href=\"sip:me@x\">link</a><a href=\"vbscript:foo\">link</a><a href=\"https://some.domain/x/a:b\">link</a>";
+
+        let result = convert(source, &ConvertOptions::new(fqdn));
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn pass_through_schemes_3() {
+        let source = "<a href=\"my_app:open\">link</a><a href=\"other_app:open\">link</a>";
+        let fqdn = "some.domain";
+        // replaces schemes set before
+        let options = ConvertOptions::new(fqdn)
+            .set_pass_through_schemes(&["my_app"])
+            .set_pass_through_schemes(&["other_app"]);
+        let expect = "<a href=\"https://some.domain/my_app:open\">link</a><a href=\"other_app:open\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn whitespace_1() {
+        let source = "<a href=\" https://other.domain/x \">link</a><a href=\" #section\">link</a><a href=\"\n/somewhere \">link</a>";
+        let fqdn = "some.domain";
+        let expect = "<a href=\" https://other.domain/x \">link</a><a href=\" #section\">link</a><a href=\"https://some.domain/somewhere\">link</a>";
+
+        let result = convert(source, &ConvertOptions::new(fqdn));
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn fragment_1() {
+        let source = "<a href=\"#section\">link</a><a href=\"other#section\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_current_path("/docs/page");
+        let expect = "<a href=\"#section\">link</a><a href=\"https://some.domain/docs/other#section\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn fragment_2() {
+        let source = "<a href=\"#section\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_current_path("/docs/page")
+            .set_keep_fragment_links(false);
+        let expect = "<a href=\"https://some.domain/docs/page#section\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn srcset_1() {
+        let source = "<img src=\"a.png\" srcset=\"a.png 1x, images/b.png 2x,/c.png  3x\">";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_current_path("/docs/")
+            .set_tags(Vec::from_iter([ConvertTag::Img]));
+        let expect = "<img src=\"https://some.domain/docs/a.png\" srcset=\"https://some.domain/docs/a.png 1x, https://some.domain/docs/images/b.png 2x,https://some.domain/c.png  3x\">";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn srcset_2() {
+        // commas in url, url without descriptor, and pass-through url
+        let source = "<img srcset=\"a,b.png 480w, c.png, data:image/png;base64,iVBO 2x\">";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_tags(Vec::from_iter([ConvertTag::Img]));
+        let expect = "<img srcset=\"https://some.domain/a,b.png 480w, https://some.domain/c.png, data:image/png;base64,iVBO 2x\">";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn srcset_3() {
+        let source = "<img srcset=\"a.png 1x\">";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_tags(Vec::from_iter([ConvertTag::Img]))
+            .set_srcset(false);
+        let expect = "<img srcset=\"a.png 1x\">";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn imagesrcset_1() {
+        let source = "<link rel=\"preload\" as=\"image\" imagesrcset=\"a.png 480w, b.png 800w\" imagesizes=\"50vw\">";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_tags(Vec::from_iter([ConvertTag::Link]));
+        let expect = "<link rel=\"preload\" as=\"image\" imagesrcset=\"https://some.domain/a.png 480w, https://some.domain/b.png 800w\" imagesizes=\"50vw\">";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_picture_1() {
+        let source = "<picture><source srcset=\"a.webp 1x, b.webp 2x\" type=\"image/webp\"><img src=\"a.png\"></picture><img src=\"c.png\">";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_tags(Vec::from_iter([ConvertTag::Picture]));
+        let expect = "<picture><source srcset=\"https://some.domain/a.webp 1x, https://some.domain/b.webp 2x\" type=\"image/webp\"><img src=\"https://some.domain/a.png\"></picture><img src=\"c.png\">";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_style_attr_1() {
+        let source = "<div style=\"background:url(img/bg.png) no-repeat; border-image: URL( '/b.png' );\">x</div>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_current_path("/docs/")
+            .set_tags(Vec::from_iter([ConvertTag::Style]));
+        let expect = "<div style=\"background:url(https://some.domain/docs/img/bg.png) no-repeat; border-image: URL( 'https://some.domain/b.png' );\">x</div>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_style_element_1() {
+        let source = "<style>@import \"x.css\";\n@import url('y.css') screen;\n/* url(comment.png) */\n.a::before { content: \"url(string.png)\"; background: url(\"a.png\"), url(data:image/png;base64,iVBO), url(#grad); }\n.b { mask: myurl(b.png); }</style>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_tags(Vec::from_iter([ConvertTag::Style]));
+        let expect = "<style>@import \"https://some.domain/x.css\";\n@import url('https://some.domain/y.css') screen;\n/* url(comment.png) */\n.a::before { content: \"url(string.png)\"; background: url(\"https://some.domain/a.png\"), url(data:image/png;base64,iVBO), url(#grad); }\n.b { mask: myurl(b.png); }</style>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_style_skipped_1() {
+        let source = "<div style=\"background:url(img/bg.png)\">x</div><style>a { background: url(a.png); }</style>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn);
+        let expect = "<div style=\"background:url(img/bg.png)\">x</div><style>a { background: url(a.png); }</style>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_all_1() {
+        let source = "<form action=\"post\"><button formaction=\"/alt\">b</button><input type=\"image\" src=\"i.png\" formaction=\"x\"><input type=\"text\" src=\"t.png\"></form>
+<iframe src=\"frame.html\"></iframe><embed src=\"e.swf\"><object data=\"o.svg\"></object>
+<video poster=\"p.png\"><track src=\"t.vtt\"></video><map><area href=\"area\"></map>
+<blockquote cite=\"bq\">x</blockquote><q cite=\"q\">x</q><del cite=\"d\">x</del><ins cite=\"i\">x</ins>
+<svg><use href=\"#icon\"></use><use xlink:href=\"s.svg#icon\"></use><image href=\"s.png\"></image></svg>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_current_path("/docs/")
+            .set_tags(ConvertTag::all());
+        let expect = "<form action=\"https://some.domain/docs/post\"><button formaction=\"https://some.domain/alt\">b</button><input type=\"image\" src=\"https://some.domain/docs/i.png\" formaction=\"https://some.domain/docs/x\"><input type=\"text\" src=\"t.png\"></form>
+<iframe src=\"https://some.domain/docs/frame.html\"></iframe><embed src=\"https://some.domain/docs/e.swf\"><object data=\"https://some.domain/docs/o.svg\"></object>
+<video poster=\"https://some.domain/docs/p.png\"><track src=\"https://some.domain/docs/t.vtt\"></video><map><area href=\"https://some.domain/docs/area\"></map>
+<blockquote cite=\"https://some.domain/docs/bq\">x</blockquote><q cite=\"https://some.domain/docs/q\">x</q><del cite=\"https://some.domain/docs/d\">x</del><ins cite=\"https://some.domain/docs/i\">x</ins>
+<svg><use href=\"#icon\"></use><use xlink:href=\"https://some.domain/docs/s.svg#icon\"></use><image href=\"https://some.domain/docs/s.png\"></image></svg>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_meta_1() {
+        let source = "<meta http-equiv=\"Refresh\" content=\"5; URL='next.html'\"><meta http-equiv=\"refresh\" content=\"0;url=/top\"><meta http-equiv=\"refresh\" content=\"30\"><meta name=\"description\" content=\"5; url=x\">";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_current_path("/docs/")
+            .set_tags(Vec::from_iter([ConvertTag::Meta]));
+        let expect = "<meta http-equiv=\"Refresh\" content=\"5; URL='https://some.domain/docs/next.html'\"><meta http-equiv=\"refresh\" content=\"0;url=https://some.domain/top\"><meta http-equiv=\"refresh\" content=\"30\"><meta name=\"description\" content=\"5; url=x\">";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn add_tag_attr_1() {
+        let source = "<div data-href=\"/somewhere\">x</div><a href=\"/somewhere\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_tags(Vec::new())
+            .add_tag_attr("div", "data-href");
+        let expect = "<div data-href=\"https://some.domain/somewhere\">x</div><a href=\"/somewhere\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn base_1() {
+        let source = "<base href=\"/docs/v2/\"><a href=\"intro\">link</a><a href=\"/top\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_current_path("/other/");
+        let expect = "<base href=\"/docs/v2/\"><a href=\"https://some.domain/docs/v2/intro\">link</a><a href=\"https://some.domain/top\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn base_2() {
+        let source = "<base href=\"v2/\"><a href=\"../intro\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_current_path("/docs/page");
+        let expect = "<base href=\"v2/\"><a href=\"https://some.domain/docs/intro\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn base_3() {
+        let source = "<base target=\"_blank\"><base href=\"https://other.domain:8080/assets/\"><base href=\"/ignored/\"><a href=\"x.png\">link</a><a href=\"/y.png\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn);
+        let expect = "<base target=\"_blank\"><base href=\"https://other.domain:8080/assets/\"><base href=\"/ignored/\"><a href=\"https://other.domain:8080/assets/x.png\">link</a><a href=\"https://other.domain:8080/y.png\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn base_4() {
+        let source = "<base href=\"//cdn.domain/assets/\"><a href=\"x.png\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_http_protocol(HttpProtocol::Http);
+        let expect = "<base href=\"//cdn.domain/assets/\"><a href=\"http://cdn.domain/assets/x.png\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn base_respect_1() {
+        let source = "<base href=\"/docs/v2/\"><a href=\"intro\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_current_path("/other/")
+            .set_respect_base(false);
+        let expect =
+            "<base href=\"/docs/v2/\"><a href=\"https://some.domain/other/intro\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn base_element_1() {
+        let source = "<html><head><base href=\"/docs/v2/\"></head><body><a href=\"intro\">link</a></body></html>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_base_element(BaseElement::Remove);
+        let expect = "<html><head></head><body><a href=\"https://some.domain/docs/v2/intro\">link</a></body></html>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn base_element_2() {
+        let source = "<base href=\"/docs/v2/\"><a href=\"intro\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_base_element(BaseElement::Absolutize);
+        let expect = "<base href=\"https://some.domain/docs/v2/\"><a href=\"https://some.domain/docs/v2/intro\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
 }
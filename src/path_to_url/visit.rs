@@ -0,0 +1,194 @@
+use markup5ever_rcdom::{Handle, NodeData};
+
+use crate::core::css::css_url_ranges;
+use crate::core::url::{refresh_url_range, srcset_url_ranges};
+
+use super::{ConvertTag, CONVERT_TAG_ATTRS, CONVERT_TAG_SRCSET_ATTRS, PICTURE_TAGS};
+
+/// how url is held in document
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UrlKind {
+    /// whole attr value such as `href`
+    Attr,
+    /// candidate in `srcset`
+    Srcset,
+    /// `url()` or `@import` in `style` attr or element
+    Css,
+    /// url in `content` of `<meta http-equiv="refresh">`
+    Refresh,
+}
+
+/// url found in document
+pub(crate) struct UrlRef<'a> {
+    pub element: &'a Handle,
+    pub tag: &'a str,
+    /// `None` on text of `style` element
+    pub attr: Option<&'a str>,
+    pub kind: UrlKind,
+    pub url: &'a str,
+}
+
+/// which attrs are url targets
+pub(crate) struct UrlTargets<'a> {
+    tags: Vec<&'static str>,
+    tag_attrs: &'a [(String, String)],
+    srcset: bool,
+}
+impl<'a> UrlTargets<'a> {
+    pub fn new(tags: &[ConvertTag], tag_attrs: &'a [(String, String)], srcset: bool) -> Self {
+        UrlTargets {
+            tags: tags
+                .iter()
+                .flat_map(|x| x.tag_names().iter().copied())
+                .collect(),
+            tag_attrs,
+            srcset,
+        }
+    }
+}
+
+/// calls `f` on each url in document recursively. url is replaced with returned value if any
+pub(crate) fn visit_urls<F>(handle: &Handle, targets: &UrlTargets, f: &mut F)
+where
+    F: FnMut(&UrlRef) -> Option<String>,
+{
+    scan(handle, targets, f, false)
+}
+
+fn scan<F>(handle: &Handle, targets: &UrlTargets, f: &mut F, in_picture: bool)
+where
+    F: FnMut(&UrlRef) -> Option<String>,
+{
+    let node = handle;
+    let mut in_picture = in_picture;
+
+    if let NodeData::Element {
+        ref name,
+        ref attrs,
+        ref template_contents,
+        ..
+    } = node.data
+    {
+        let tag_name = name.local.as_ref();
+
+        let is_target_tag = targets.tags.contains(&tag_name)
+            || (in_picture
+                && targets.tags.contains(&"picture")
+                && PICTURE_TAGS.contains(&tag_name));
+
+        // attrs are copied so that `f` can read them
+        let current_attrs = attrs.borrow().clone();
+        let attr_value = |attr_name: &str| {
+            current_attrs
+                .iter()
+                .find(|x| x.name.local.as_ref() == attr_name)
+                .map(|x| x.value.as_ref())
+        };
+        // `src` of `input` is url only on `type="image"`
+        let is_image_input = tag_name == "input"
+            && attr_value("type").is_some_and(|x| x.eq_ignore_ascii_case("image"));
+        let is_refresh = tag_name == "meta"
+            && attr_value("http-equiv").is_some_and(|x| x.eq_ignore_ascii_case("refresh"));
+
+        for (i, attr) in current_attrs.iter().enumerate() {
+            let attr_name = attr.name.local.as_ref();
+            let value = attr.value.as_ref();
+            let is_custom_attr = targets
+                .tag_attrs
+                .iter()
+                .any(|(tag, attr)| tag == tag_name && attr == attr_name);
+            let is_style_attr = attr_name == "style" && targets.tags.contains(&"style");
+            if !is_target_tag && !is_custom_attr && !is_style_attr {
+                continue;
+            }
+            let mut visit = |kind: UrlKind, url: &str| {
+                f(&UrlRef {
+                    element: node,
+                    tag: tag_name,
+                    attr: Some(attr_name),
+                    kind,
+                    url,
+                })
+            };
+
+            let replaced = if is_custom_attr
+                || (is_target_tag && CONVERT_TAG_ATTRS.contains(&(tag_name, attr_name)))
+            {
+                if tag_name == "input" && attr_name == "src" && !is_image_input {
+                    continue;
+                }
+                visit(UrlKind::Attr, value)
+            } else if is_style_attr {
+                replace_ranges(value, css_url_ranges(value), |x| visit(UrlKind::Css, x))
+            } else if targets.srcset && CONVERT_TAG_SRCSET_ATTRS.contains(&(tag_name, attr_name)) {
+                replace_ranges(value, srcset_url_ranges(value), |x| {
+                    visit(UrlKind::Srcset, x)
+                })
+            } else if is_refresh && attr_name == "content" {
+                let ranges = refresh_url_range(value).into_iter().collect();
+                replace_ranges(value, ranges, |x| visit(UrlKind::Refresh, x))
+            } else {
+                None
+            };
+
+            if let Some(replaced) = replaced {
+                attrs.borrow_mut()[i].value = replaced.into();
+            }
+        }
+
+        if tag_name == "style" && targets.tags.contains(&"style") {
+            for child in node.children.borrow().iter() {
+                if let NodeData::Text { ref contents } = child.data {
+                    let css = contents.borrow().to_string();
+                    let replaced = replace_ranges(&css, css_url_ranges(&css), |x| {
+                        f(&UrlRef {
+                            element: node,
+                            tag: tag_name,
+                            attr: None,
+                            kind: UrlKind::Css,
+                            url: x,
+                        })
+                    });
+                    if let Some(replaced) = replaced {
+                        *contents.borrow_mut() = replaced.into();
+                    }
+                }
+            }
+        }
+
+        if let Some(contents) = template_contents.borrow().as_ref() {
+            scan(contents, targets, f, in_picture);
+        }
+
+        if tag_name == "picture" {
+            in_picture = true;
+        }
+    }
+
+    for child in node.children.borrow().iter() {
+        scan(child, targets, f, in_picture);
+    }
+}
+
+/// replaces text in ranges with value returned by `f`. returns `None` if nothing is replaced
+fn replace_ranges<F>(text: &str, ranges: Vec<std::ops::Range<usize>>, mut f: F) -> Option<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut replaced = false;
+    for range in ranges {
+        if let Some(x) = f(&text[range.clone()]) {
+            output.push_str(&text[last..range.start]);
+            output.push_str(&x);
+            last = range.end;
+            replaced = true;
+        }
+    }
+    if !replaced {
+        return None;
+    }
+    output.push_str(&text[last..]);
+    Some(output)
+}
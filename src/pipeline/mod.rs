@@ -0,0 +1,137 @@
+pub use markup5ever_rcdom::{Handle, NodeData, RcDom};
+
+pub use crate::core::serialize::VoidStyle;
+
+use crate::core::serialize::serialize;
+use crate::core::utils::{fragment_root, parse, parse_as_fragment, reset_document_outline};
+use crate::Error;
+
+mod tests;
+
+/// dom manipulation applied by `Pipeline`
+///
+/// implement this to add your own pass:
+///
+/// ```rust
+/// use htmlproc::pipeline::{Pipeline, Processor, RcDom};
+/// use htmlproc::Error;
+///
+/// struct Noop;
+/// impl Processor for Noop {
+///     fn process(&self, _dom: &mut RcDom) -> Result<(), Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// let result = Pipeline::new().add_processor(Noop).run("<p>Content</p>");
+/// assert_eq!(result.unwrap(), "<p>Content</p>");
+/// ```
+pub trait Processor {
+    /// manipulates dom in place
+    fn process(&self, dom: &mut RcDom) -> Result<(), Error>;
+}
+
+/// how to parse input html
+#[derive(Clone, Copy, Default)]
+pub enum InputMode<'a> {
+    /// parses as document and omits `html`, `head` and `body` unless their start tags are in input
+    #[default]
+    Auto,
+    /// parses as document and keeps its outline
+    Document,
+    /// parses as children of `context` element such as "body" or "tr" and keeps them as they are
+    Fragment { context: &'a str },
+}
+impl InputMode<'_> {
+    /// parses html text into dom
+    pub(crate) fn parse(&self, html: &str) -> RcDom {
+        match self {
+            InputMode::Fragment { context } => parse_as_fragment(html, context),
+            _ => parse(html),
+        }
+    }
+}
+
+/// parses html once, applies processors in order and serializes once
+///
+/// ```rust
+/// use htmlproc::omit_attr::OmitAttr;
+/// use htmlproc::omit_enclosure::OmitEnclosure;
+/// use htmlproc::path_to_url::{ConvertOptions, PathToUrl};
+/// use htmlproc::pipeline::Pipeline;
+///
+/// let source: &str = "<div><span style=\"want: omitted;\"><a href=\"/somewhere\">link</a></span></div>";
+/// let expect: &str = "<div><a href=\"https://some.domain/somewhere\">link</a></div>";
+///
+/// let result = Pipeline::new()
+///     .add_processor(OmitAttr::new(&["style"]))
+///     .add_processor(OmitEnclosure::new(&["span"]))
+///     .add_processor(PathToUrl::new(ConvertOptions::new("some.domain")))
+///     .run(source);
+/// assert_eq!(result.unwrap(), expect);
+/// ```
+///
+/// `InputMode::Fragment` keeps fragments exactly as they are:
+///
+/// ```rust
+/// use htmlproc::omit_attr::OmitAttr;
+/// use htmlproc::pipeline::{InputMode, Pipeline};
+///
+/// let source: &str = "<td style=\"want: omitted;\">Cell</td>";
+/// let expect: &str = "<td>Cell</td>";
+///
+/// let result = Pipeline::new()
+///     .set_input_mode(InputMode::Fragment { context: "tr" })
+///     .add_processor(OmitAttr::new(&["style"]))
+///     .run(source);
+/// assert_eq!(result.unwrap(), expect);
+/// ```
+#[derive(Default)]
+pub struct Pipeline<'a> {
+    processors: Vec<Box<dyn Processor>>,
+    input_mode: InputMode<'a>,
+    void_style: VoidStyle,
+}
+impl<'a> Pipeline<'a> {
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+    /// appends processor to run after the ones already added
+    pub fn add_processor<P: Processor + 'static>(mut self, processor: P) -> Self {
+        self.processors.push(Box::new(processor));
+        self
+    }
+    /// sets how to parse input html. Default: `InputMode::Auto`
+    pub fn set_input_mode(mut self, input_mode: InputMode<'a>) -> Self {
+        self.input_mode = input_mode;
+        self
+    }
+    /// sets how to write void elements such as `br`. Default: `VoidStyle::Html` (`<br>`)
+    pub fn set_void_style(mut self, void_style: VoidStyle) -> Self {
+        self.void_style = void_style;
+        self
+    }
+    /// runs processors on html text
+    pub fn run(&self, html: &str) -> Result<String, Error> {
+        let mut dom = self.input_mode.parse(html);
+
+        for processor in self.processors.iter() {
+            processor.process(&mut dom)?;
+        }
+
+        let output = match self.input_mode {
+            InputMode::Auto => {
+                reset_document_outline(&dom.document, html);
+                serialize(&dom.document, self.void_style)
+            }
+            InputMode::Document => serialize(&dom.document, self.void_style),
+            InputMode::Fragment { .. } => serialize(&fragment_root(&dom.document), self.void_style),
+        };
+        Ok(output)
+    }
+    /// runs processors on html bytes which should be UTF-8
+    pub fn run_bytes(&self, html: &[u8]) -> Result<String, Error> {
+        let html = std::str::from_utf8(html)?;
+        self.run(html)
+    }
+}
@@ -0,0 +1,226 @@
+/// `cargo test --features full`
+#[cfg(test)]
+mod tests {
+    use crate::omit_attr::OmitAttr;
+    use crate::omit_enclosure::OmitEnclosure;
+    use crate::path_to_url::{ConvertOptions, ConvertTag, PathToUrl};
+    use crate::pipeline::{Handle, InputMode, NodeData, Pipeline, Processor, RcDom, VoidStyle};
+    use crate::Error;
+
+    /// uppercases text for testing third-party processor
+    struct Uppercase;
+    impl Processor for Uppercase {
+        fn process(&self, dom: &mut RcDom) -> Result<(), Error> {
+            fn scan(handle: &Handle) {
+                if let NodeData::Text { ref contents } = handle.data {
+                    let uppercased = contents.borrow().to_uppercase();
+                    *contents.borrow_mut() = uppercased.into();
+                }
+                for child in handle.children.borrow().iter() {
+                    scan(child);
+                }
+            }
+            scan(&dom.document);
+            Ok(())
+        }
+    }
+
+    /// always fails for testing error propagation
+    struct Failing;
+    impl Processor for Failing {
+        fn process(&self, _dom: &mut RcDom) -> Result<(), Error> {
+            Err(Error::Processor("failed".into()))
+        }
+    }
+
+    #[test]
+    fn run_1() {
+        let source = "<div><span style=\"remove: me;\">Content</span></div>";
+        let expect = "<div><span style=\"remove: me;\">Content</span></div>";
+
+        let result = Pipeline::new().run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn run_2() {
+        let source = "<div class=\"keep-me\"><span style=\"remove: me;\"><img src=\"/some.file\"></span></div>";
+        let expect = "<div class=\"keep-me\"><img src=\"https://some.domain/some.file\"></div>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&["style"]))
+            .add_processor(OmitEnclosure::new(&["span"]))
+            .add_processor(PathToUrl::new(
+                ConvertOptions::new("some.domain").set_tags(Vec::from_iter([ConvertTag::Img])),
+            ))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn run_order_1() {
+        // attrs of omitted enclosure are gone regardless of order
+        let source = "<div><span style=\"remove: me;\">Content</span></div>";
+        let expect = "<div>Content</div>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitEnclosure::new(&["span"]))
+            .add_processor(OmitAttr::new(&["style"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn document_outline_1() {
+        let source = "<html><head></head><body><span style=\"remove: me;\"><a href=\"/somewhere\">link</a></span></body></html>";
+        let expect = "<html><head></head><body><a href=\"https://some.domain/somewhere\">link</a></body></html>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&["style"]))
+            .add_processor(OmitEnclosure::new(&["span"]))
+            .add_processor(PathToUrl::new(ConvertOptions::new("some.domain")))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn custom_processor_1() {
+        let source = "<p title=\"keep\">Content</p>";
+        let expect = "<p title=\"keep\">CONTENT</p>";
+
+        let result = Pipeline::new().add_processor(Uppercase).run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn error_1() {
+        let source = "<p>Content</p>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&["a.b.c"]))
+            .add_processor(Uppercase)
+            .run(source);
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+
+        let result = Pipeline::new().add_processor(Failing).run(source);
+        assert!(matches!(result, Err(Error::Processor(_))));
+    }
+
+    #[test]
+    fn run_bytes_1() {
+        let result = Pipeline::new().run_bytes("<p>Content</p>".as_bytes());
+        assert_eq!(result.unwrap(), "<p>Content</p>");
+
+        let result = Pipeline::new().run_bytes(&[b'<', b'p', b'>', 0xff]);
+        assert!(matches!(result, Err(Error::InvalidUtf8(_))));
+    }
+
+    #[test]
+    fn input_mode_auto_1() {
+        let source = "<HTML lang=\"en\"><Body class=\"page\"><span style=\"remove: me;\">Content</span></Body></HTML>";
+        let expect = "<html lang=\"en\"><body class=\"page\"><span>Content</span></body></html>";
+
+        let result = Pipeline::new()
+            .add_processor(OmitAttr::new(&["style"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn input_mode_auto_2() {
+        // outline tags in comments, attribute values and raw text are not start tags
+        let source = "<!-- <html> --><p title=\"<body>\">Content</p><script>let s = \"<head>\";</script><textarea><body></textarea>";
+        let expect = "<!-- <html> --><p title=\"&lt;body&gt;\">Content</p><script>let s = \"<head>\";</script><textarea>&lt;body&gt;</textarea>";
+
+        let result = Pipeline::new().run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn input_mode_document_1() {
+        let source = "<span style=\"remove: me;\">Content</span>";
+        let expect = "<html><head></head><body><span>Content</span></body></html>";
+
+        let result = Pipeline::new()
+            .set_input_mode(InputMode::Document)
+            .add_processor(OmitAttr::new(&["style"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn input_mode_document_2() {
+        let source = "<!DOCTYPE html><html lang=\"en\"><head><title>Title</title></head><body class=\"page\"><p>Content</p></body></html>";
+        let expect = "<!DOCTYPE html><html lang=\"en\"><head><title>Title</title></head><body class=\"page\"><p>Content</p></body></html>";
+
+        let result = Pipeline::new()
+            .set_input_mode(InputMode::Document)
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn input_mode_fragment_1() {
+        // text mentioning outline tags does not affect output
+        let source = "<p>use &lt;body&gt; and <span style=\"remove: me;\">&lt;html&gt;</span></p>";
+        let expect = "<p>use &lt;body&gt; and <span>&lt;html&gt;</span></p>";
+
+        let result = Pipeline::new()
+            .set_input_mode(InputMode::Fragment { context: "body" })
+            .add_processor(OmitAttr::new(&["style"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn input_mode_fragment_2() {
+        let source = "<tr><td>Cell</td></tr>";
+        let expect = "<tr><td>Cell</td></tr>";
+
+        let result = Pipeline::new()
+            .set_input_mode(InputMode::Fragment { context: "tbody" })
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn input_mode_fragment_3() {
+        let source = "<p>Content</p>";
+        let expect = "Content";
+
+        let result = Pipeline::new()
+            .set_input_mode(InputMode::Fragment { context: "body" })
+            .add_processor(OmitEnclosure::new(&["html", "p"]))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn void_style_1() {
+        let source = "<head><base href=\"/\"></head><p>a<br>b<img src=\"x.png\"></p>";
+        let expect = "<head><base href=\"/\"></head><p>a<br>b<img src=\"x.png\"></p>";
+
+        let result = Pipeline::new().set_void_style(VoidStyle::Html).run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn void_style_2() {
+        let source = "<head><base href=\"/\"></head><p>a<br>b<img src=\"x.png\"></p>";
+        let expect = "<head><base href=\"/\"/></head><p>a<br/>b<img src=\"x.png\"/></p>";
+
+        let result = Pipeline::new().set_void_style(VoidStyle::Slash).run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn void_style_3() {
+        let source = "<head><base href=\"/\"></head><p>a<br>b<img src=\"x.png\"></p>";
+        let expect = "<head><base href=\"/\" /></head><p>a<br />b<img src=\"x.png\" /></p>";
+
+        let result = Pipeline::new()
+            .set_void_style(VoidStyle::SpaceSlash)
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+}
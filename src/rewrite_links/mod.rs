@@ -0,0 +1,342 @@
+use markup5ever_rcdom::RcDom;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::core::glob::Glob;
+use crate::core::preserve::filter_nodes;
+use crate::path_to_url::{visit_urls, ConvertTag, UrlTargets};
+use crate::pipeline::{InputMode, Pipeline, Processor};
+use crate::{CommentPolicy, Error};
+
+mod tests;
+
+/// how rule matches link
+#[derive(Clone)]
+enum RuleKind {
+    /// whole link equals
+    Exact,
+    /// link starts with
+    Prefix,
+    /// link matches glob pattern whose `*` are captured
+    Pattern,
+}
+
+/// rewrite rule
+#[derive(Clone)]
+struct Rule {
+    kind: RuleKind,
+    from: String,
+    to: String,
+}
+
+/// rule ready to apply
+struct ActualRule<'a> {
+    rule: &'a Rule,
+    /// compiled `from` of pattern rule
+    glob: Option<Glob>,
+}
+impl ActualRule<'_> {
+    /// rewritten link when matched
+    fn apply(&self, link: &str) -> Option<String> {
+        let rule = self.rule;
+        match (&rule.kind, &self.glob) {
+            (RuleKind::Exact, _) => (link == rule.from).then(|| rule.to.to_owned()),
+            (RuleKind::Prefix, _) => link
+                .strip_prefix(rule.from.as_str())
+                .map(|rest| format!("{}{}", rule.to, rest)),
+            (RuleKind::Pattern, Some(glob)) => glob
+                .captures(link)
+                .map(|captures| expand(&rule.to, &captures)),
+            (RuleKind::Pattern, None) => None,
+        }
+    }
+}
+
+/// result of rewriting
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RewriteReport {
+    /// number of links rewritten by each rule, in order of rules
+    pub fired: Vec<usize>,
+    /// rewritten links as (rule index, original link, rewritten link) in document order
+    pub rewritten: Vec<(usize, String, String)>,
+    /// links matched by no rule in document order
+    pub unmatched: Vec<String>,
+}
+
+/// rewrite options
+#[derive(Clone)]
+pub struct RewriteOptions {
+    /// rules tried in order. first matched one is applied
+    rules: Vec<Rule>,
+    /// target tags. Default: all
+    tags: Vec<ConvertTag>,
+    /// additional target attr of each tag, rewritten regardless of `tags`
+    tag_attrs: Vec<(String, String)>,
+    /// rewrites srcset candidates or not. Default: true
+    srcset: bool,
+    /// comments handling. Default: keep all
+    comments: CommentPolicy,
+    /// keeps doctype or not. Default: true
+    doctype: bool,
+    /// how to parse input html. Default: `InputMode::Auto`
+    input_mode: InputMode<'static>,
+}
+impl Default for RewriteOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl RewriteOptions {
+    pub fn new() -> Self {
+        RewriteOptions {
+            rules: Vec::new(),
+            tags: ConvertTag::all(),
+            tag_attrs: Vec::new(),
+            srcset: true,
+            comments: CommentPolicy::Keep,
+            doctype: true,
+            input_mode: InputMode::Auto,
+        }
+    }
+    /// rewrites `from` such as `/old-page` into `to`
+    pub fn add_exact(mut self, from: &str, to: &str) -> Self {
+        self.rules.push(Rule {
+            kind: RuleKind::Exact,
+            from: from.to_owned(),
+            to: to.to_owned(),
+        });
+        self
+    }
+    /// rewrites `from` such as `/blog/` at the start of link into `to`
+    pub fn add_prefix(mut self, from: &str, to: &str) -> Self {
+        self.rules.push(Rule {
+            kind: RuleKind::Prefix,
+            from: from.to_owned(),
+            to: to.to_owned(),
+        });
+        self
+    }
+    /// rewrites link matching `from` such as `/blog/*/*.html` into `to` such as `/news/$1/$2`
+    ///
+    /// `*` matches any characters (`\*` for literal `*`), and `$1` to `$9` in `to` are replaced
+    /// with them in order (`$$` for literal `$`)
+    pub fn add_pattern(mut self, from: &str, to: &str) -> Self {
+        self.rules.push(Rule {
+            kind: RuleKind::Pattern,
+            from: from.to_owned(),
+            to: to.to_owned(),
+        });
+        self
+    }
+    pub fn set_tags(mut self, tags: Vec<ConvertTag>) -> Self {
+        self.tags = tags;
+        self
+    }
+    /// adds rewrite target attr of tag such as `("div", "data-href")`
+    pub fn add_tag_attr(mut self, tag: &str, attr: &str) -> Self {
+        self.tag_attrs.push((tag.to_owned(), attr.to_owned()));
+        self
+    }
+    /// `srcset` of `img` and `source`, and `imagesrcset` of `link`
+    pub fn set_srcset(mut self, srcset: bool) -> Self {
+        self.srcset = srcset;
+        self
+    }
+    pub fn set_comments(mut self, comments: CommentPolicy) -> Self {
+        self.comments = comments;
+        self
+    }
+    pub fn set_doctype(mut self, doctype: bool) -> Self {
+        self.doctype = doctype;
+        self
+    }
+    /// sets how to parse input html. Default: `InputMode::Auto`
+    pub fn set_input_mode(mut self, input_mode: InputMode<'static>) -> Self {
+        self.input_mode = input_mode;
+        self
+    }
+}
+
+/// rewrites links in html according to rules
+///
+/// [feature entry point]
+///
+/// rules match path part of links, and query and fragment are kept.
+/// rules whose `from` has `?` or `#` match whole links instead.
+///
+/// ```rust
+/// use htmlproc::rewrite_links::{rewrite, RewriteOptions};
+///
+/// let source: &str = "<a href=\"/old-page?x=1\">link</a><a href=\"/blog/2024/post.html\">link</a><a href=\"/about\">link</a>";
+/// let options = RewriteOptions::new()
+///     .add_exact("/old-page", "/new-page")
+///     .add_pattern("/blog/*/*.html", "/news/$1/$2/");
+/// let expect: &str = "<a href=\"/new-page?x=1\">link</a><a href=\"/news/2024/post/\">link</a><a href=\"/about\">link</a>";
+///
+/// let (result, report) = rewrite(source, &options);
+/// assert_eq!(result, expect);
+/// assert_eq!(report.fired, vec![1, 1]);
+/// assert_eq!(report.unmatched, vec!["/about"]);
+/// ```
+///
+/// panics when `options` has invalid pattern. use `try_rewrite` to handle it as error.
+pub fn rewrite(html: &str, options: &RewriteOptions) -> (String, RewriteReport) {
+    try_rewrite(html, options).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// rewrites links in html according to rules, returning error instead of panic
+///
+/// ```rust
+/// use htmlproc::rewrite_links::{try_rewrite, RewriteOptions};
+/// use htmlproc::Error;
+///
+/// let source: &str = "<a href=\"/blog/post\">link</a>";
+///
+/// let result = try_rewrite(source, &RewriteOptions::new().add_pattern("/blog/*", "/news/$1"));
+/// assert_eq!(result.unwrap().0, "<a href=\"/news/post\">link</a>");
+///
+/// let result = try_rewrite(source, &RewriteOptions::new().add_pattern("/blog/*", "/news/$2"));
+/// assert!(matches!(result, Err(Error::InvalidPattern(_))));
+/// ```
+///
+pub fn try_rewrite(html: &str, options: &RewriteOptions) -> Result<(String, RewriteReport), Error> {
+    let processor = RewriteLinks::new(options.clone());
+    let report = processor.report();
+    let html = Pipeline::new()
+        .set_input_mode(options.input_mode)
+        .add_processor(processor)
+        .run(html)?;
+    let report = report.take();
+    Ok((html, report))
+}
+
+/// `Processor` rewriting links, for use in `Pipeline`
+pub struct RewriteLinks {
+    options: RewriteOptions,
+    report: Rc<RefCell<RewriteReport>>,
+}
+impl RewriteLinks {
+    pub fn new(options: RewriteOptions) -> Self {
+        RewriteLinks {
+            options,
+            report: Rc::new(RefCell::new(RewriteReport::default())),
+        }
+    }
+    /// report shared with this processor, filled on process
+    ///
+    /// take it before adding this processor to `Pipeline` and read it after run
+    pub fn report(&self) -> Rc<RefCell<RewriteReport>> {
+        self.report.clone()
+    }
+}
+impl Processor for RewriteLinks {
+    fn process(&self, dom: &mut RcDom) -> Result<(), Error> {
+        let options = &self.options;
+        let rules = rules(&options.rules)?;
+
+        let mut report = RewriteReport {
+            fired: vec![0; rules.len()],
+            ..Default::default()
+        };
+        let targets = UrlTargets::new(&options.tags, &options.tag_attrs, options.srcset);
+        visit_urls(&dom.document, &targets, &mut |x| {
+            let link = x.url.trim();
+            match rewrite_link(&rules, link) {
+                Some((i, rewritten)) => {
+                    report.fired[i] += 1;
+                    report
+                        .rewritten
+                        .push((i, link.to_owned(), rewritten.clone()));
+                    Some(rewritten)
+                }
+                None => {
+                    report.unmatched.push(link.to_owned());
+                    None
+                }
+            }
+        });
+        *self.report.borrow_mut() = report;
+
+        filter_nodes(&dom.document, options.comments, options.doctype);
+        Ok(())
+    }
+}
+
+/// validates specified rules and compiles patterns
+fn rules(rules: &[Rule]) -> Result<Vec<ActualRule<'_>>, Error> {
+    rules
+        .iter()
+        .map(|rule| {
+            if rule.from.is_empty() {
+                return Err(Error::InvalidPattern(String::from(
+                    "from of rewrite rule is empty",
+                )));
+            }
+            let glob = match rule.kind {
+                RuleKind::Pattern => {
+                    let glob = Glob::new(&rule.from)?;
+                    validate_replacement(&rule.to, glob.stars())?;
+                    Some(glob)
+                }
+                _ => None,
+            };
+            Ok(ActualRule { rule, glob })
+        })
+        .collect()
+}
+
+/// index of first matched rule and rewritten link
+fn rewrite_link(rules: &[ActualRule], link: &str) -> Option<(usize, String)> {
+    let suffix_start = link.find(['?', '#']).unwrap_or(link.len());
+    let (path, suffix) = link.split_at(suffix_start);
+    rules.iter().enumerate().find_map(|(i, rule)| {
+        if rule.rule.from.contains(['?', '#']) {
+            rule.apply(link).map(|x| (i, x))
+        } else {
+            rule.apply(path).map(|x| (i, format!("{}{}", x, suffix)))
+        }
+    })
+}
+
+/// checks `$n` in replacement refers to existing capture
+fn validate_replacement(to: &str, stars: usize) -> Result<(), Error> {
+    let mut chars = to.chars();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            continue;
+        }
+        match chars.next() {
+            Some('$') => {}
+            Some(n) if n.is_ascii_digit() && n != '0' && (n as usize - '0' as usize) <= stars => {}
+            _ => {
+                return Err(Error::InvalidPattern(format!(
+                    "\"$\" in replacement should be followed by \"$\" or capture number 1 to {}: \"{}\"",
+                    stars, to
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// replaces `$n` in replacement with captures
+fn expand(to: &str, captures: &[&str]) -> String {
+    let mut output = String::with_capacity(to.len());
+    let mut chars = to.chars();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('$') => output.push('$'),
+            Some(n) => {
+                let i = n as usize - '1' as usize;
+                output.push_str(captures.get(i).unwrap_or(&""));
+            }
+            None => {}
+        }
+    }
+    output
+}
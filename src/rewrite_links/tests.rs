@@ -0,0 +1,157 @@
+/// `cargo test --features rewrite_links`
+#[cfg(test)]
+mod tests {
+    use crate::path_to_url::ConvertTag;
+    use crate::pipeline::Pipeline;
+    use crate::rewrite_links::{rewrite, try_rewrite, RewriteLinks, RewriteOptions};
+    use crate::Error;
+
+    #[test]
+    fn exact_1() {
+        let source = "<a href=\"/old-page\">link</a><a href=\"/old-page#top\">link</a><a href=\"/old-page/x\">link</a>";
+        let options = RewriteOptions::new().add_exact("/old-page", "/new-page");
+        let expect = "<a href=\"/new-page\">link</a><a href=\"/new-page#top\">link</a><a href=\"/old-page/x\">link</a>";
+
+        let (result, report) = rewrite(source, &options);
+        assert_eq!(result, expect);
+        assert_eq!(report.fired, vec![2]);
+        assert_eq!(report.unmatched, vec!["/old-page/x"]);
+    }
+
+    #[test]
+    fn exact_2() {
+        let source = "<a href=\"/page?id=1\">link</a><a href=\"/page?id=2\">link</a>";
+        let options = RewriteOptions::new().add_exact("/page?id=1", "/first");
+        let expect = "<a href=\"/first\">link</a><a href=\"/page?id=2\">link</a>";
+
+        let (result, _) = rewrite(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn prefix_1() {
+        let source = "<a href=\"/blog/2024/post?x=1\">link</a><img src=\"/blog/img.png\">";
+        let options = RewriteOptions::new().add_prefix("/blog/", "/news/");
+        let expect = "<a href=\"/news/2024/post?x=1\">link</a><img src=\"/news/img.png\">";
+
+        let (result, report) = rewrite(source, &options);
+        assert_eq!(result, expect);
+        assert_eq!(
+            report.rewritten,
+            vec![
+                (
+                    0,
+                    String::from("/blog/2024/post?x=1"),
+                    String::from("/news/2024/post?x=1")
+                ),
+                (
+                    0,
+                    String::from("/blog/img.png"),
+                    String::from("/news/img.png")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn pattern_1() {
+        let source = "<a href=\"https://old.domain/?p=12\">link</a><a href=\"/cat/news/item-3.php\">link</a>";
+        let options = RewriteOptions::new()
+            .add_pattern("https://old.domain/?p=*", "/posts/$1/")
+            .add_pattern("/cat/*/item-*.php", "/$1/$2$$");
+        let expect = "<a href=\"/posts/12/\">link</a><a href=\"/news/3$\">link</a>";
+
+        let (result, report) = rewrite(source, &options);
+        assert_eq!(result, expect);
+        assert_eq!(report.fired, vec![1, 1]);
+    }
+
+    #[test]
+    fn pattern_2() {
+        let source = "<a href=\"/a*b\">link</a><a href=\"/axb\">link</a>";
+        let options = RewriteOptions::new().add_pattern("/a\\*b", "/star");
+        let expect = "<a href=\"/star\">link</a><a href=\"/axb\">link</a>";
+
+        let (result, _) = rewrite(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn pattern_many_stars_1() {
+        // backtracking takes exponential time on such patterns
+        let link = format!("/{}", "a".repeat(60));
+        let source = format!(
+            "<a href=\"{}\">link</a><a href=\"{}b\">link</a>",
+            link, link
+        );
+        let options =
+            RewriteOptions::new().add_pattern(&format!("/{}b", "*a".repeat(20)), "/$1/$2");
+        let expect = format!("<a href=\"{}\">link</a><a href=\"//\">link</a>", link);
+
+        let (result, report) = rewrite(&source, &options);
+        assert_eq!(result, expect);
+        assert_eq!(report.fired, vec![1]);
+    }
+
+    #[test]
+    fn order_1() {
+        let source = "<a href=\"/docs/index\">link</a>";
+        let options = RewriteOptions::new()
+            .add_exact("/docs/index", "/manual/")
+            .add_prefix("/docs/", "/guide/");
+        let expect = "<a href=\"/manual/\">link</a>";
+
+        let (result, report) = rewrite(source, &options);
+        assert_eq!(result, expect);
+        assert_eq!(report.fired, vec![1, 0]);
+    }
+
+    #[test]
+    fn srcset_1() {
+        let source = "<img srcset=\"/old/a.png 1x, /keep/b.png 2x\"><div style=\"background: url(/old/bg.png)\"></div>";
+        let options = RewriteOptions::new().add_prefix("/old/", "/new/");
+        let expect = "<img srcset=\"/new/a.png 1x, /keep/b.png 2x\"><div style=\"background: url(/new/bg.png)\"></div>";
+
+        let (result, report) = rewrite(source, &options);
+        assert_eq!(result, expect);
+        assert_eq!(report.unmatched, vec!["/keep/b.png"]);
+    }
+
+    #[test]
+    fn set_tags_1() {
+        let source = "<a href=\"/old/x\">link</a><img src=\"/old/y.png\">";
+        let options = RewriteOptions::new()
+            .add_prefix("/old/", "/new/")
+            .set_tags(vec![ConvertTag::Img]);
+        let expect = "<a href=\"/old/x\">link</a><img src=\"/new/y.png\">";
+
+        let (result, _) = rewrite(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn pipeline_1() {
+        let source = "<a href=\"/old/x\">link</a>";
+        let processor = RewriteLinks::new(RewriteOptions::new().add_prefix("/old/", "/new/"));
+        let report = processor.report();
+        let expect = "<a href=\"/new/x\">link</a>";
+
+        let result = Pipeline::new().add_processor(processor).run(source);
+        assert_eq!(result.unwrap(), expect);
+        assert_eq!(report.borrow().fired, vec![1]);
+    }
+
+    #[test]
+    fn invalid_pattern_1() {
+        let source = "<a href=\"/old/x\">link</a>";
+
+        let result = try_rewrite(source, &RewriteOptions::new().add_exact("", "/x"));
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+
+        let result = try_rewrite(source, &RewriteOptions::new().add_pattern("/old\\", "/x"));
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+
+        let result = try_rewrite(source, &RewriteOptions::new().add_pattern("/*", "/$0"));
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+    }
+}
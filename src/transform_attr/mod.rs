@@ -0,0 +1,306 @@
+use html5ever::{namespace_url, ns, Attribute, LocalName, Prefix, QualName};
+use markup5ever_rcdom::{Handle, NodeData, RcDom};
+
+use std::rc::Rc;
+
+use crate::core::attr_rule::{rule, AttrRule};
+use crate::core::preserve::filter_nodes;
+use crate::core::utils::attr_name;
+use crate::pipeline::{InputMode, Pipeline, Processor};
+use crate::{CommentPolicy, Error};
+
+mod tests;
+
+/// closure returning new value from attr name and value. `None` removes attr
+type AttrFn = Rc<dyn Fn(&str, &str) -> Option<String>>;
+
+/// transformation of attrs matching rule
+#[derive(Clone)]
+enum Transform {
+    /// renames attr, replacing existing attr of the new name
+    Rename(String),
+    /// sets value, adding attr when missing
+    Set(String),
+    /// appends token to whitespace-separated value, adding attr when missing
+    AppendToken(String),
+    /// replaces value with closure result
+    Apply(AttrFn),
+}
+
+/// transform options
+#[derive(Clone)]
+pub struct TransformOptions {
+    /// rules in the same format as `omit_attr` and their transformations, applied in order
+    transforms: Vec<(String, Transform)>,
+    /// comments handling. Default: keep all
+    comments: CommentPolicy,
+    /// keeps doctype or not. Default: true
+    doctype: bool,
+    /// how to parse input html. Default: `InputMode::Auto`
+    input_mode: InputMode<'static>,
+}
+impl Default for TransformOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl TransformOptions {
+    pub fn new() -> Self {
+        TransformOptions {
+            transforms: Vec::new(),
+            comments: CommentPolicy::Keep,
+            doctype: true,
+            input_mode: InputMode::Auto,
+        }
+    }
+    /// renames attrs matching `rule` such as `img.data-src` to `to` such as `src`.
+    /// existing attr named `to` is replaced. `rule` having `*` in attr name and `to` which can't
+    /// be written as attr name such as `a b` are invalid
+    pub fn add_rename(mut self, rule: &str, to: &str) -> Self {
+        self.transforms
+            .push((rule.to_owned(), Transform::Rename(to.to_owned())));
+        self
+    }
+    /// sets `value` to attrs matching `rule` such as `img.loading`.
+    /// attr is added when missing, unless `rule` has `*` in attr name or value condition
+    pub fn add_set(mut self, rule: &str, value: &str) -> Self {
+        self.transforms
+            .push((rule.to_owned(), Transform::Set(value.to_owned())));
+        self
+    }
+    /// appends `token` such as `noopener` to attrs matching `rule` such as `a[href^=http] rel`
+    /// unless they have it. attr is added as `add_set` does
+    pub fn add_append_token(mut self, rule: &str, token: &str) -> Self {
+        self.transforms
+            .push((rule.to_owned(), Transform::AppendToken(token.to_owned())));
+        self
+    }
+    /// replaces value of attrs matching `rule` with result of `f` called with attr name and value.
+    /// `None` removes attr
+    pub fn add_fn<F>(mut self, rule: &str, f: F) -> Self
+    where
+        F: Fn(&str, &str) -> Option<String> + 'static,
+    {
+        self.transforms
+            .push((rule.to_owned(), Transform::Apply(Rc::new(f))));
+        self
+    }
+    pub fn set_comments(mut self, comments: CommentPolicy) -> Self {
+        self.comments = comments;
+        self
+    }
+    pub fn set_doctype(mut self, doctype: bool) -> Self {
+        self.doctype = doctype;
+        self
+    }
+    /// sets how to parse input html. Default: `InputMode::Auto`
+    pub fn set_input_mode(mut self, input_mode: InputMode<'static>) -> Self {
+        self.input_mode = input_mode;
+        self
+    }
+}
+
+/// renames attributes and transforms their values in html
+///
+/// [feature entry point]
+///
+/// rules are in the same format as `omit_attr`: `attr`, `tag.attr` or `selector attr`,
+/// or with value condition such as `a[target=_blank]`. each element gets transformations
+/// in order, so later rules see the results of earlier ones.
+///
+/// ```rust
+/// use htmlproc::transform_attr::{transform, TransformOptions};
+///
+/// let source: &str = "<img src=\"dummy.gif\" data-src=\"photo.jpg\"><a href=\"https://other.domain\" rel=\"external\">link</a>";
+/// let options = TransformOptions::new()
+///     .add_rename("img.data-src", "src")
+///     .add_set("img.loading", "lazy")
+///     .add_append_token("a[href^=http] rel", "noopener");
+/// let expect: &str = "<img src=\"photo.jpg\" loading=\"lazy\"><a href=\"https://other.domain\" rel=\"external noopener\">link</a>";
+///
+/// let result = transform(source, &options);
+/// assert_eq!(result, expect);
+/// ```
+///
+/// panics when `options` has invalid rule. use `try_transform` to handle it as error.
+pub fn transform(html: &str, options: &TransformOptions) -> String {
+    try_transform(html, options).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// renames attributes and transforms their values in html, returning error instead of panic
+///
+/// ```rust
+/// use htmlproc::transform_attr::{try_transform, TransformOptions};
+/// use htmlproc::Error;
+///
+/// let source: &str = "<p title=\"Hello\">Content</p>";
+///
+/// let options = TransformOptions::new().add_fn("p.title", |_, value| Some(value.to_uppercase()));
+/// let result = try_transform(source, &options);
+/// assert_eq!(result.unwrap(), "<p title=\"HELLO\">Content</p>");
+///
+/// let result = try_transform(source, &TransformOptions::new().add_set("a.b.c", "x"));
+/// assert!(matches!(result, Err(Error::InvalidPattern(_))));
+/// ```
+///
+pub fn try_transform(html: &str, options: &TransformOptions) -> Result<String, Error> {
+    Pipeline::new()
+        .set_input_mode(options.input_mode)
+        .add_processor(TransformAttr::new(options.clone()))
+        .run(html)
+}
+
+/// `Processor` renaming attributes and transforming their values, for use in `Pipeline`
+pub struct TransformAttr {
+    options: TransformOptions,
+}
+impl TransformAttr {
+    pub fn new(options: TransformOptions) -> Self {
+        TransformAttr { options }
+    }
+}
+impl Processor for TransformAttr {
+    fn process(&self, dom: &mut RcDom) -> Result<(), Error> {
+        let transforms = self
+            .options
+            .transforms
+            .iter()
+            .map(|(pattern, transform)| {
+                let rule = rule(pattern)?;
+                if let Transform::Rename(to) = transform {
+                    // several attrs can't be renamed to one
+                    if rule.attr.literal().is_none() {
+                        return Err(Error::InvalidPattern(format!(
+                            "rename rule should not have \"*\" in attr name: \"{}\"",
+                            pattern
+                        )));
+                    }
+                    if !is_valid_attr_name(to) {
+                        return Err(Error::InvalidPattern(format!(
+                            "invalid attr name to rename to: \"{}\"",
+                            to
+                        )));
+                    }
+                }
+                Ok((rule, transform))
+            })
+            .collect::<Result<Vec<(AttrRule, &Transform)>, Error>>()?;
+
+        walk(&dom.document, &transforms);
+        filter_nodes(&dom.document, self.options.comments, self.options.doctype);
+        Ok(())
+    }
+}
+
+/// transforms attrs of elements recursively
+fn walk(handle: &Handle, transforms: &[(AttrRule, &Transform)]) {
+    let node = handle;
+
+    if let NodeData::Element {
+        ref name,
+        ref attrs,
+        ref template_contents,
+        ..
+    } = node.data
+    {
+        for (rule, transform) in transforms {
+            // selector borrows attrs, so it is matched before they are changed
+            if rule.scope.matches(name, node) {
+                apply(&mut attrs.borrow_mut(), rule, transform);
+            }
+        }
+
+        if let Some(contents) = template_contents.borrow().as_ref() {
+            walk(contents, transforms);
+        }
+    }
+
+    for child in node.children.borrow().iter() {
+        walk(child, transforms);
+    }
+}
+
+fn apply(attrs: &mut Vec<Attribute>, rule: &AttrRule, transform: &Transform) {
+    let matched = (0..attrs.len())
+        .filter(|&i| rule.matches(&attrs[i]))
+        .collect::<Vec<usize>>();
+
+    match transform {
+        Transform::Rename(to) => {
+            let renamed = match matched.first() {
+                Some(&i) => i,
+                None => return,
+            };
+            attrs[renamed].name = qual_name(to);
+            // existing attr of the new name
+            let removed = (0..attrs.len())
+                .filter(|&i| i != renamed && attr_name(&attrs[i].name) == *to)
+                .collect::<Vec<usize>>();
+            for i in removed.into_iter().rev() {
+                attrs.remove(i);
+            }
+        }
+        Transform::Set(value) => {
+            for &i in matched.iter() {
+                attrs[i].value = value.as_str().into();
+            }
+            if matched.is_empty() {
+                add_missing(attrs, rule, value);
+            }
+        }
+        Transform::AppendToken(token) => {
+            for &i in matched.iter() {
+                if !attrs[i].value.split_ascii_whitespace().any(|x| x == token) {
+                    let value = format!("{} {}", attrs[i].value.trim_end(), token);
+                    attrs[i].value = value.trim_start().into();
+                }
+            }
+            if matched.is_empty() {
+                add_missing(attrs, rule, token);
+            }
+        }
+        Transform::Apply(f) => {
+            for &i in matched.iter().rev() {
+                match f(&attr_name(&attrs[i].name), &attrs[i].value) {
+                    Some(value) => attrs[i].value = value.into(),
+                    None => {
+                        attrs.remove(i);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// adds attr of rule when its name is literal and it has no value condition
+fn add_missing(attrs: &mut Vec<Attribute>, rule: &AttrRule, value: &str) {
+    if rule.value.is_some() {
+        return;
+    }
+    if let Some(name) = rule.attr.literal() {
+        attrs.push(Attribute {
+            name: qual_name(name),
+            value: value.into(),
+        });
+    }
+}
+
+/// checks if attr name can be written in markup as it is
+fn is_valid_attr_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '>' | '/' | '=' | '<'))
+}
+
+/// attr name such as `src`, or `xlink:href` having namespace
+fn qual_name(name: &str) -> QualName {
+    let (ns, local) = match name.split_once(':') {
+        Some(("xlink", local)) => (ns!(xlink), local),
+        Some(("xml", local)) => (ns!(xml), local),
+        Some(("xmlns", local)) => (ns!(xmlns), local),
+        _ => return QualName::new(None, ns!(), LocalName::from(name)),
+    };
+    let prefix = &name[..name.len() - local.len() - 1];
+    QualName::new(Some(Prefix::from(prefix)), ns, LocalName::from(local))
+}
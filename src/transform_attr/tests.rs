@@ -0,0 +1,200 @@
+/// `cargo test --features transform_attr`
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::{InputMode, Pipeline};
+    use crate::transform_attr::{transform, try_transform, TransformAttr, TransformOptions};
+    use crate::Error;
+
+    #[test]
+    fn rename_1() {
+        let source = "<img data-src=\"photo.jpg\" alt=\"a\">";
+        let options = TransformOptions::new().add_rename("img.data-src", "src");
+        let expect = "<img src=\"photo.jpg\" alt=\"a\">";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn rename_2() {
+        let source = "<img src=\"dummy.gif\" data-src=\"photo.jpg\" data-srcset=\"photo-2x.jpg 2x\"><div data-src=\"x\"></div>";
+        let options = TransformOptions::new()
+            .add_rename("img.data-src", "src")
+            .add_rename("img.data-srcset", "srcset");
+        let expect = "<img src=\"photo.jpg\" srcset=\"photo-2x.jpg 2x\"><div data-src=\"x\"></div>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn rename_3() {
+        let source = "<img src=\"dummy.gif\">";
+        let options = TransformOptions::new().add_rename("img.data-src", "src");
+        let expect = "<img src=\"dummy.gif\">";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn set_1() {
+        let source = "<img src=\"a.png\"><img src=\"b.png\" loading=\"eager\"><p>text</p>";
+        let options = TransformOptions::new().add_set("img.loading", "lazy");
+        let expect =
+            "<img src=\"a.png\" loading=\"lazy\"><img src=\"b.png\" loading=\"lazy\"><p>text</p>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn set_2() {
+        let source = "<a href=\"/x\" target=\"_self\">x</a><a href=\"/y\">y</a>";
+        let options = TransformOptions::new().add_set("a[target=_self]", "_blank");
+        let expect = "<a href=\"/x\" target=\"_blank\">x</a><a href=\"/y\">y</a>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn set_3() {
+        let source = "<div data-a=\"1\" data-b=\"2\"></div><p></p>";
+        let options = TransformOptions::new().add_set("data-*", "0");
+        let expect = "<div data-a=\"0\" data-b=\"0\"></div><p></p>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn append_token_1() {
+        let source = "<a href=\"https://other.domain\">x</a><a href=\"https://other.domain\" rel=\"external\">y</a><a href=\"https://other.domain\" rel=\"noopener\">z</a><a href=\"/local\">w</a>";
+        let options = TransformOptions::new().add_append_token("a[href^=http] rel", "noopener");
+        let expect = "<a href=\"https://other.domain\" rel=\"noopener\">x</a><a href=\"https://other.domain\" rel=\"external noopener\">y</a><a href=\"https://other.domain\" rel=\"noopener\">z</a><a href=\"/local\">w</a>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn fn_1() {
+        let source = "<a href=\"HTTP://Some.Domain/x\" onclick=\"f()\">x</a>";
+        let options = TransformOptions::new()
+            .add_fn("a.href", |_, value| Some(value.to_lowercase()))
+            .add_fn("on*", |_, _| None);
+        let expect = "<a href=\"http://some.domain/x\">x</a>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn fn_2() {
+        let source = "<div data-x=\"1\" data-y=\"2\"></div>";
+        let options = TransformOptions::new().add_fn("div.data-*", |name, value| {
+            Some(format!("{}:{}", name, value))
+        });
+        let expect = "<div data-x=\"data-x:1\" data-y=\"data-y:2\"></div>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn order_1() {
+        let source = "<img data-src=\"photo.jpg\">";
+        let options = TransformOptions::new()
+            .add_rename("img.data-src", "src")
+            .add_fn("img[src] src", |_, value| {
+                Some(format!("/images/{}", value))
+            });
+        let expect = "<img src=\"/images/photo.jpg\">";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn template_1() {
+        let source = "<template><img data-src=\"photo.jpg\"></template>";
+        let options = TransformOptions::new().add_rename("img.data-src", "src");
+        let expect = "<template><img src=\"photo.jpg\"></template>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn pipeline_1() {
+        let source = "<img data-src=\"photo.jpg\">";
+        let expect = "<img src=\"photo.jpg\">";
+
+        let result = Pipeline::new()
+            .add_processor(TransformAttr::new(
+                TransformOptions::new().add_rename("data-src", "src"),
+            ))
+            .run(source);
+        assert_eq!(result.unwrap(), expect);
+    }
+
+    #[test]
+    fn input_mode_1() {
+        let source = "<td data-x=\"1\">Cell</td>";
+        let options = TransformOptions::new()
+            .add_rename("td.data-x", "title")
+            .set_input_mode(InputMode::Fragment { context: "tr" });
+        let expect = "<td title=\"1\">Cell</td>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn invalid_rule_1() {
+        let source = "<img>";
+
+        let result = try_transform(source, &TransformOptions::new().add_set("div[ img.x", "y"));
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn invalid_rule_2() {
+        let source = "<img data-src=\"a.jpg\" data-alt=\"a\">";
+
+        let result = try_transform(
+            source,
+            &TransformOptions::new().add_rename("img.data-*", "src"),
+        );
+        assert!(matches!(result, Err(Error::InvalidPattern(_))));
+
+        let options = TransformOptions::new().add_rename("img.data-\\*", "src");
+        let result = try_transform(source, &options);
+        assert_eq!(result.unwrap(), source);
+    }
+
+    #[test]
+    fn invalid_rule_3() {
+        let source = "<img data-src=\"a.jpg\">";
+
+        for to in ["", "bad name", "x\"", "x'", "x>", "x/", "x=y"] {
+            let options = TransformOptions::new().add_rename("img.data-src", to);
+            let result = try_transform(source, &options);
+            assert!(matches!(result, Err(Error::InvalidPattern(_))), "{}", to);
+        }
+    }
+
+    #[test]
+    fn namespace_1() {
+        let source = "<svg><use xlink:href=\"#a\"></use><image data-href=\"a.png\"></image></svg>";
+        let options = TransformOptions::new()
+            .add_rename("svg|use.xlink:href", "href")
+            .add_rename("svg|image.data-href", "xlink:href")
+            .add_set("svg.viewBox", "0 0 10 10");
+        let expect = "<svg viewBox=\"0 0 10 10\"><use href=\"#a\"></use><image xlink:href=\"a.png\"></image></svg>";
+
+        let result = transform(source, &options);
+        assert_eq!(result, expect);
+    }
+}
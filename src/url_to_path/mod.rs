@@ -0,0 +1,256 @@
+use markup5ever_rcdom::RcDom;
+
+use crate::core::preserve::filter_nodes;
+use crate::core::url::{
+    relativize, remove_dot_segments, scheme, split_host_port, split_origin, validate_url_options,
+};
+use crate::path_to_url::{visit_urls, ConvertTag, HttpProtocol, UrlTargets};
+use crate::pipeline::{InputMode, Pipeline, Processor};
+use crate::{CommentPolicy, Error};
+
+mod tests;
+
+/// form of converted paths
+#[derive(Clone, Copy, Default)]
+pub enum PathMode {
+    /// `/assets/x.png`
+    #[default]
+    RootRelative,
+    /// `../assets/x.png` from `current_path`
+    DocumentRelative,
+}
+
+/// conversion options
+#[derive(Clone)]
+pub struct ConvertOptions {
+    /// owned hosts (host name, domain). urls on other hosts are left untouched
+    hosts: Vec<String>,
+    /// owned HTTP protocols. Default: both
+    http_protocols: Vec<HttpProtocol>,
+    /// owned ports besides the default port of each protocol
+    ports: Vec<u16>,
+    /// path of current document. Default: /
+    /// treated as directory only when it ends with `/` (`/docs/page` relativizes `/docs/x` to `x`)
+    current_path: String,
+    /// form of converted paths. Default: root-relative
+    path_mode: PathMode,
+    /// target tags
+    tags: Vec<ConvertTag>,
+    /// additional target attr of each tag, converted regardless of `tags`
+    tag_attrs: Vec<(String, String)>,
+    /// converts srcset candidates or not. Default: true
+    srcset: bool,
+    /// comments handling. Default: keep all
+    comments: CommentPolicy,
+    /// keeps doctype or not. Default: true
+    doctype: bool,
+    /// how to parse input html. Default: `InputMode::Auto`
+    input_mode: InputMode<'static>,
+}
+impl ConvertOptions {
+    pub fn new(fqdn: &str) -> Self {
+        ConvertOptions {
+            hosts: Vec::from_iter([fqdn.to_owned()]),
+            http_protocols: Vec::from_iter([HttpProtocol::Http, HttpProtocol::Https]),
+            ports: Vec::new(),
+            current_path: String::from("/"),
+            path_mode: PathMode::RootRelative,
+            tags: Vec::from_iter([ConvertTag::A]),
+            tag_attrs: Vec::new(),
+            srcset: true,
+            comments: CommentPolicy::Keep,
+            doctype: true,
+            input_mode: InputMode::Auto,
+        }
+    }
+    /// adds owned host such as `www.some.domain`
+    pub fn add_host(mut self, host: &str) -> Self {
+        self.hosts.push(host.to_owned());
+        self
+    }
+    pub fn set_http_protocols(mut self, http_protocols: Vec<HttpProtocol>) -> Self {
+        self.http_protocols = http_protocols;
+        self
+    }
+    /// adds owned port such as `8080`. default port of protocol is always owned
+    pub fn add_port(mut self, port: u16) -> Self {
+        self.ports.push(port);
+        self
+    }
+    pub fn set_current_path(mut self, current_path: &str) -> Self {
+        self.current_path = current_path.to_owned();
+        self
+    }
+    pub fn set_path_mode(mut self, path_mode: PathMode) -> Self {
+        self.path_mode = path_mode;
+        self
+    }
+    pub fn set_tags(mut self, tags: Vec<ConvertTag>) -> Self {
+        self.tags = tags;
+        self
+    }
+    /// adds conversion target attr of tag such as `("div", "data-href")`
+    pub fn add_tag_attr(mut self, tag: &str, attr: &str) -> Self {
+        self.tag_attrs.push((tag.to_owned(), attr.to_owned()));
+        self
+    }
+    /// `srcset` of `img` and `source`, and `imagesrcset` of `link`
+    pub fn set_srcset(mut self, srcset: bool) -> Self {
+        self.srcset = srcset;
+        self
+    }
+    pub fn set_comments(mut self, comments: CommentPolicy) -> Self {
+        self.comments = comments;
+        self
+    }
+    pub fn set_doctype(mut self, doctype: bool) -> Self {
+        self.doctype = doctype;
+        self
+    }
+    /// sets how to parse input html. Default: `InputMode::Auto`
+    pub fn set_input_mode(mut self, input_mode: InputMode<'static>) -> Self {
+        self.input_mode = input_mode;
+        self
+    }
+
+    /// checks if origin such as `https://some.domain:8080` or `//some.domain` is owned
+    fn is_owned_origin(&self, scheme: Option<&str>, origin: &str) -> bool {
+        let default_port = match scheme {
+            Some(x) if x.eq_ignore_ascii_case("http") => 80,
+            Some(x) if x.eq_ignore_ascii_case("https") => 443,
+            Some(_) => return false,
+            // network-path reference inherits scheme of document
+            None => 0,
+        };
+        let is_owned_protocol = self.http_protocols.iter().any(|x| match x {
+            HttpProtocol::Http => default_port != 443,
+            HttpProtocol::Https => default_port != 80,
+        });
+        if !is_owned_protocol {
+            return false;
+        }
+
+        let (host, port) = split_host_port(origin);
+        // userinfo such as `user@` makes it another resource
+        if host.contains('@') {
+            return false;
+        }
+        let is_owned_port = match port {
+            "" => true,
+            port => port.parse::<u16>().is_ok_and(|x| {
+                x == default_port
+                    || (default_port == 0 && (x == 80 || x == 443))
+                    || self.ports.contains(&x)
+            }),
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        is_owned_port
+            && self.hosts.iter().any(|x| {
+                x.trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .eq_ignore_ascii_case(host)
+            })
+    }
+
+    /// converts owned url to path. returns `None` when value is not owned url
+    fn convert(&self, value: &str) -> Option<String> {
+        let (origin, rest) = split_origin(value)?;
+        if !self.is_owned_origin(scheme(value), origin) {
+            return None;
+        }
+        let rest = if rest.starts_with('/') {
+            rest.to_owned()
+        } else {
+            format!("/{}", rest)
+        };
+        match self.path_mode {
+            PathMode::RootRelative => {
+                let i = rest.find(['?', '#']).unwrap_or(rest.len());
+                Some(format!("{}{}", remove_dot_segments(&rest[..i]), &rest[i..]))
+            }
+            PathMode::DocumentRelative => Some(relativize(&self.current_path, &rest)),
+        }
+    }
+}
+
+/// convert urls on owned hosts in html to paths
+///
+/// [feature entry point]
+///
+/// ```rust
+/// use htmlproc::url_to_path::{convert, ConvertOptions, PathMode};
+///
+/// let source: &str = "<a href=\"https://some.domain/assets/x.png\">link</a>";
+/// let fqdn: &str = "some.domain";
+///
+/// let options: ConvertOptions = ConvertOptions::new(fqdn);
+/// let result = convert(source, &options);
+/// assert_eq!(result, "<a href=\"/assets/x.png\">link</a>");
+///
+/// let options: ConvertOptions = ConvertOptions::new(fqdn)
+///     .set_current_path("/docs/page.html")
+///     .set_path_mode(PathMode::DocumentRelative);
+/// let result = convert(source, &options);
+/// assert_eq!(result, "<a href=\"../assets/x.png\">link</a>");
+/// ```
+///
+/// panics when `options` is invalid. use `try_convert` to handle it as error.
+pub fn convert(html: &str, options: &ConvertOptions) -> String {
+    try_convert(html, options).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// convert urls on owned hosts in html to paths, returning error instead of panic
+///
+/// ```rust
+/// use htmlproc::url_to_path::{try_convert, ConvertOptions};
+/// use htmlproc::Error;
+///
+/// let source: &str = "<a href=\"https://some.domain/somewhere\">link</a>";
+///
+/// let result = try_convert(source, &ConvertOptions::new("some.domain"));
+/// assert_eq!(result.unwrap(), "<a href=\"/somewhere\">link</a>");
+///
+/// let result = try_convert(source, &ConvertOptions::new("some.domain").set_current_path("docs"));
+/// assert!(matches!(result, Err(Error::InvalidUrlOption(_))));
+/// ```
+///
+pub fn try_convert(html: &str, options: &ConvertOptions) -> Result<String, Error> {
+    Pipeline::new()
+        .set_input_mode(options.input_mode)
+        .add_processor(UrlToPath::new(options.clone()))
+        .run(html)
+}
+
+/// `Processor` converting urls to paths, for use in `Pipeline`
+pub struct UrlToPath {
+    options: ConvertOptions,
+}
+impl UrlToPath {
+    pub fn new(options: ConvertOptions) -> Self {
+        UrlToPath { options }
+    }
+}
+impl Processor for UrlToPath {
+    fn process(&self, dom: &mut RcDom) -> Result<(), Error> {
+        let options = &self.options;
+        validate(options)?;
+
+        let targets = UrlTargets::new(&options.tags, &options.tag_attrs, options.srcset);
+        visit_urls(&dom.document, &targets, &mut |x| {
+            options.convert(x.url.trim())
+        });
+
+        filter_nodes(&dom.document, options.comments, options.doctype);
+        Ok(())
+    }
+}
+
+/// validates specified options
+fn validate(options: &ConvertOptions) -> Result<(), Error> {
+    let hosts = options
+        .hosts
+        .iter()
+        .map(|x| x.as_str())
+        .collect::<Vec<&str>>();
+    validate_url_options(&hosts, &options.ports, &options.current_path)
+}
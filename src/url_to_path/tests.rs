@@ -0,0 +1,145 @@
+/// `cargo test --features url_to_path`
+#[cfg(test)]
+mod tests {
+    use crate::path_to_url::{ConvertTag, HttpProtocol};
+    use crate::url_to_path::{convert, try_convert, ConvertOptions, PathMode};
+    use crate::Error;
+
+    #[test]
+    fn convert_a_1() {
+        let source = "<a href=\"https://some.domain/somewhere\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn);
+        let expect = "<a href=\"/somewhere\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_a_2() {
+        let source = "<a href=\"http://SOME.domain/some/./where/../x?q=1#f\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn);
+        let expect = "<a href=\"/some/x?q=1#f\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_a_3() {
+        let source = "<a href=\"https://some.domain\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn);
+        let expect = "<a href=\"/\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn other_host_1() {
+        let source = "<a href=\"https://other.domain/somewhere\">link</a><a href=\"https://user@some.domain/x\">link</a><a href=\"mailto:x@some.domain\">mail</a><a href=\"/already\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn);
+        let expect = source;
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn add_host_1() {
+        let source =
+            "<a href=\"https://www.some.domain/x\">link</a><a href=\"//some.domain/y\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).add_host("www.some.domain");
+        let expect = "<a href=\"/x\">link</a><a href=\"/y\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn http_protocols_1() {
+        let source =
+            "<a href=\"http://some.domain/x\">link</a><a href=\"https://some.domain/y\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_http_protocols(vec![HttpProtocol::Https]);
+        let expect = "<a href=\"http://some.domain/x\">link</a><a href=\"/y\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn port_1() {
+        let source = "<a href=\"https://some.domain:443/x\">link</a><a href=\"https://some.domain:8443/y\">link</a><a href=\"http://some.domain:8080/z\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).add_port(8443);
+        let expect = "<a href=\"/x\">link</a><a href=\"/y\">link</a><a href=\"http://some.domain:8080/z\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn ipv6_1() {
+        let source = "<a href=\"http://[::1]:8080/x\">link</a>";
+        let fqdn = "::1";
+        let options = ConvertOptions::new(fqdn).add_port(8080);
+        let expect = "<a href=\"/x\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn document_relative_1() {
+        let source = "<a href=\"https://some.domain/assets/x.png\">link</a><a href=\"https://some.domain/docs/other.html#f\">link</a><a href=\"https://some.domain/docs/\">link</a><a href=\"https://some.domain/docs/page.html?q\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_current_path("/docs/page.html")
+            .set_path_mode(PathMode::DocumentRelative);
+        let expect = "<a href=\"../assets/x.png\">link</a><a href=\"other.html#f\">link</a><a href=\"./\">link</a><a href=\"page.html?q\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn document_relative_2() {
+        let source = "<a href=\"https://some.domain/docs/v2/a:b\">link</a><a href=\"https://some.domain/\">link</a>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn)
+            .set_current_path("/docs/v2/")
+            .set_path_mode(PathMode::DocumentRelative);
+        let expect = "<a href=\"./a:b\">link</a><a href=\"../../\">link</a>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn convert_all_1() {
+        let source = "<img src=\"https://some.domain/a.png\" srcset=\"https://some.domain/a.png 1x, https://cdn.domain/b.png 2x\"><div style=\"background: url(https://some.domain/bg.png)\"></div>";
+        let fqdn = "some.domain";
+        let options = ConvertOptions::new(fqdn).set_tags(ConvertTag::all());
+        let expect = "<img src=\"/a.png\" srcset=\"/a.png 1x, https://cdn.domain/b.png 2x\"><div style=\"background: url(/bg.png)\"></div>";
+
+        let result = convert(source, &options);
+        assert_eq!(result, expect);
+    }
+
+    #[test]
+    fn invalid_option_1() {
+        let source = "<a href=\"https://some.domain/somewhere\">link</a>";
+
+        let result = try_convert(source, &ConvertOptions::new("some.domain:8080"));
+        assert!(matches!(result, Err(Error::InvalidUrlOption(_))));
+
+        let result = try_convert(source, &ConvertOptions::new("some.domain").add_host(""));
+        assert!(matches!(result, Err(Error::InvalidUrlOption(_))));
+    }
+}